@@ -0,0 +1,50 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! About dialog
+
+use kas::prelude::*;
+use kas::widgets::{Button, Label, ScrollLabel, Separator};
+
+use crate::data::Shared;
+
+/// Version of the kas dependency (see `Cargo.toml`)
+const KAS_VERSION: &str = "0.14";
+
+#[derive(Clone, Debug)]
+struct Close;
+
+pub fn window() -> Window<Shared> {
+    let ui = impl_anon! {
+        #[widget {
+            layout = column! [
+                self.title,
+                Separator::new(),
+                self.versions,
+                "Licence: New BSD License (see the COPYRIGHT file)",
+                ScrollLabel::new("7GUIs: https://eugenkiss.github.io/7guis/\nKAS: https://github.com/kas-gui/kas\nSource: https://github.com/kas-gui/7guis"),
+                align!(right, Button::label_msg("&Close", Close)),
+            ];
+        }]
+        struct {
+            core: widget_core!(),
+            #[widget] title: Label<&'static str> = Label::new("KAS 7GUIs"),
+            #[widget] versions: Label<String> = Label::new(format!(
+                "Version {}, built with KAS {KAS_VERSION}",
+                env!("CARGO_PKG_VERSION")
+            )),
+        }
+        impl Events for Self {
+            type Data = ();
+
+            fn handle_messages(&mut self, cx: &mut EventCx, _: &()) {
+                if let Some(Close) = cx.try_pop() {
+                    cx.action(self.id(), Action::CLOSE);
+                }
+            }
+        }
+    };
+    Window::new(ui.map_any(), "About")
+}
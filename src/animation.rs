@@ -0,0 +1,106 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Eased value interpolation
+//!
+//! A widget holding a [`Tween`] should, while [`Tween::is_running`], request
+//! a timer every [`FRAME`] and redraw on each.
+
+use std::time::{Duration, Instant};
+
+/// Interval between animation frames (about 60 per second)
+pub const FRAME: Duration = Duration::from_micros(16_667);
+
+/// No easing
+pub fn linear(t: f32) -> f32 {
+    t
+}
+
+/// Fast start, slow finish
+pub fn ease_out_cubic(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// An instant at least `duration` ago
+fn past(duration: Duration) -> Instant {
+    let now = Instant::now();
+    now.checked_sub(duration).unwrap_or(now)
+}
+
+/// A value which moves smoothly towards a target
+#[derive(Clone, Copy, Debug)]
+pub struct Tween {
+    from: f32,
+    to: f32,
+    start: Instant,
+    duration: Duration,
+    ease: fn(f32) -> f32,
+}
+
+impl Tween {
+    /// Construct, at rest at `value`
+    ///
+    /// Animations take the given `duration` and use [`ease_out_cubic`].
+    pub fn new(value: f32, duration: Duration) -> Self {
+        Tween {
+            from: value,
+            to: value,
+            start: past(duration),
+            duration,
+            ease: ease_out_cubic,
+        }
+    }
+
+    /// Set the easing function, mapping `0.0..=1.0` to `0.0..=1.0`
+    pub fn with_ease(mut self, ease: fn(f32) -> f32) -> Self {
+        self.ease = ease;
+        self
+    }
+
+    /// Start animating from the current value to `to`
+    pub fn set(&mut self, to: f32) {
+        self.from = self.value();
+        self.to = to;
+        self.start = Instant::now();
+    }
+
+    /// Change the target without restarting the animation
+    ///
+    /// This suits a moving target: any animation in progress continues
+    /// smoothly towards it, otherwise the value simply follows.
+    pub fn retarget(&mut self, to: f32) {
+        if !self.is_running() {
+            self.from = to;
+        }
+        self.to = to;
+    }
+
+    /// Set the value immediately
+    pub fn jump(&mut self, value: f32) {
+        self.from = value;
+        self.to = value;
+        self.start = past(self.duration);
+    }
+
+    /// The target value
+    pub fn target(&self) -> f32 {
+        self.to
+    }
+
+    /// True until the target is reached
+    pub fn is_running(&self) -> bool {
+        self.start.elapsed() < self.duration
+    }
+
+    /// The current value
+    pub fn value(&self) -> f32 {
+        let t = self.start.elapsed().as_secs_f32() / self.duration.as_secs_f32();
+        if t >= 1.0 || !t.is_finite() {
+            self.to
+        } else {
+            self.from + (self.to - self.from) * (self.ease)(t)
+        }
+    }
+}
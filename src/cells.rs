@@ -4,93 +4,812 @@
 //     https://www.apache.org/licenses/LICENSE-2.0
 
 //! Cells: a mini spreadsheet
+//!
+//! Formulas may use numbers, quoted text (`"..."`, with `""` for a quote)
+//! and the `&` operator to join text.
+//!
+//! Functions are SUM, AVG, MIN, MAX and COUNT (which also take ranges like
+//! `A1:B5`), ABS, ROUND, SQRT, SIN, COS and IF. Comparisons (`=`, `<>`, `<`,
+//! `<=`, `>`, `>=`) give 1 if true and 0 if false.
+//!
+//! References like `A1` move with a formula when it is copied or filled;
+//! `$` fixes the column or row, as in `$A$1`.
+//!
+//! Names like `tax_rate` may be defined for a cell or range and used in place
+//! of references; undefined names show `#NAME?`.
+//!
+//! The cells referenced by the focused cell are highlighted, and optionally
+//! the cells referencing it.
+//!
+//! A cell may have a note, marked with `◥` and shown in the status bar on focus.
+//!
+//! An input may start with a number format: `[0.00]` (fixed decimals),
+//! `[0.0%]` (percent), `[0.00E]` (scientific) or `[general]`.
 
-use kas::event::{Command, FocusSource};
+use kas::event::{Command, ElementState, EventState, FocusSource};
 use kas::prelude::*;
-use kas::view::{DataKey, Driver, MatrixData, MatrixView, SharedData};
-use kas::widgets::{EditBox, EditField, EditGuard, ScrollBars};
-use std::collections::HashMap;
+use kas::view::{DataKey, Driver, MatrixData, MatrixView, SelectionMode, SharedData};
+use kas::widgets::{
+    Button, CheckButton, EditBox, EditField, EditGuard, RadioButton, Row, ScrollBars, Text,
+};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use std::{fmt, iter, ops};
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
-pub struct ColKey(u8);
-type ColKeyIter = iter::Map<ops::RangeInclusive<u8>, fn(u8) -> ColKey>;
+use crate::clipboard;
+use crate::data::Shared;
+use crate::demos::demo_window;
+use crate::menu::{EditAction, FileAction, MenuSpec, MenuTarget};
+use crate::shortcuts::Shortcut;
+use crate::snapshot;
+use crate::status::{Status, StatusBar};
+use crate::toast::Toast;
+use crate::undo::UndoStack;
+
+/// A column index: A to Z, then AA to ZZ
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Default, Hash)]
+pub struct ColKey(u16);
+type ColKeyIter = iter::Map<ops::Range<u16>, fn(u16) -> ColKey>;
 impl ColKey {
-    const LEN: u8 = 26;
-    fn try_from_u8(n: u8) -> Option<Self> {
-        if (b'A'..=b'Z').contains(&n) {
-            Some(ColKey(n))
-        } else {
-            None
-        }
+    const LEN: u16 = 26 * 27;
+    fn try_from_index(n: u16) -> Option<Self> {
+        (n < Self::LEN).then_some(ColKey(n))
     }
-    fn from_u8(n: u8) -> Self {
-        Self::try_from_u8(n).expect("bad column key")
+    /// Parse a column name like "AB" (case-insensitive)
+    fn parse(s: &str) -> Option<Self> {
+        if s.is_empty() || s.len() > 2 {
+            return None;
+        }
+        let mut n = 0;
+        for b in s.bytes() {
+            let digit = b
+                .to_ascii_uppercase()
+                .checked_sub(b'A')
+                .filter(|d| *d < 26)?;
+            n = n * 26 + u16::from(digit) + 1;
+        }
+        Self::try_from_index(n - 1)
     }
     fn iter_keys() -> ColKeyIter {
-        (b'A'..=b'Z').map(ColKey::from_u8)
+        (0..Self::LEN).map(ColKey)
     }
 }
 
 impl fmt::Display for ColKey {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let b = [self.0];
-        write!(f, "{}", std::str::from_utf8(&b).unwrap())
+        let n = self.0;
+        if n >= 26 {
+            write!(f, "{}", char::from(b'A' + (n / 26 - 1) as u8))?;
+        }
+        write!(f, "{}", char::from(b'A' + (n % 26) as u8))
+    }
+}
+
+const MAX_ROW: u32 = 99_999;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Key(ColKey, u32);
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.0, self.1)
+    }
+}
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Key::parse(&s).ok_or_else(|| D::Error::custom(format!("no cell {s}")))
     }
 }
+impl Key {
+    /// Parse a key like "A1" or "AB12"; the column may be lower-case
+    pub fn parse(s: &str) -> Option<Self> {
+        let split = s.find(|c: char| c.is_ascii_digit())?;
+        let col = ColKey::parse(&s[..split])?;
+        let row = s[split..].parse().ok();
+        let row = row.filter(|row| (1..=MAX_ROW).contains(row))?;
+        Some(Key(col, row))
+    }
 
-const MAX_ROW: u8 = 99;
+    /// The key `cols` columns right and `rows` rows down, if within the sheet
+    fn offset(self, cols: i32, rows: i32) -> Option<Self> {
+        let col = u16::try_from(i32::from(self.0 .0) + cols).ok();
+        let col = ColKey::try_from_index(col?)?;
+        let row = u32::try_from(i64::from(self.1) + i64::from(rows)).ok();
+        let row = row.filter(|row| (1..=MAX_ROW).contains(row))?;
+        Some(Key(col, row))
+    }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
-pub struct Key(ColKey, u8);
+    /// The offset in columns and rows from `origin` to this key
+    fn offset_from(self, origin: Key) -> (i32, i32) {
+        let cols = i32::from(self.0 .0) - i32::from(origin.0 .0);
+        // Rows do not exceed MAX_ROW
+        (cols, self.1 as i32 - origin.1 as i32)
+    }
+}
 impl DataKey for Key {
     fn make_id(&self, parent: &Id) -> Id {
-        assert_eq!(std::mem::size_of::<ColKey>(), 1);
-        let key = (((self.0).0 as usize) << 8) | (self.1 as usize);
-        parent.make_child(key)
+        let col = (self.0).0.cast();
+        parent.make_child(col).make_child(self.1.cast())
     }
 
     fn reconstruct_key(parent: &Id, child: &Id) -> Option<Self> {
-        child.next_key_after(parent).map(|key| {
-            let col = ColKey((key >> 8) as u8);
-            let row = key as u8;
-            Key(col, row)
+        let mut iter = child.iter_keys_after(parent);
+        let col = ColKey::try_from_index(iter.next()?.try_into().ok()?)?;
+        let row = iter.next()?.try_into().ok()?;
+        Some(Key(col, row))
+    }
+}
+
+/// A reference to a cell in a formula
+///
+/// `$` before the column or row makes that part absolute: it stays fixed
+/// when the formula is copied or filled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct CellRef {
+    key: Key,
+    abs_col: bool,
+    abs_row: bool,
+}
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let abs = |abs| if abs { "$" } else { "" };
+        let Key(col, row) = self.key;
+        write!(f, "{}{col}{}{row}", abs(self.abs_col), abs(self.abs_row))
+    }
+}
+impl CellRef {
+    /// Parse a reference like "A1" or "$A$1"
+    fn parse(s: &str) -> Option<Self> {
+        let abs_col = s.starts_with('$');
+        let abs_row = s[usize::from(abs_col)..].contains('$');
+        let key = Key::parse(&s.replace('$', ""))?;
+        Some(CellRef {
+            key,
+            abs_col,
+            abs_row,
         })
     }
+
+    /// Move relative parts by `cols` and `rows`, if the result is within the sheet
+    fn offset(self, cols: i32, rows: i32) -> Option<Self> {
+        let cols = if self.abs_col { 0 } else { cols };
+        let rows = if self.abs_row { 0 } else { rows };
+        let key = self.key.offset(cols, rows)?;
+        Some(CellRef { key, ..self })
+    }
 }
 
 fn make_key(k: &str) -> Key {
-    let col = ColKey::from_u8(k.as_bytes()[0]);
-    let row: u8 = k[1..].parse().unwrap();
-    Key(col, row)
+    Key::parse(k).expect("bad cell key")
 }
 
 #[derive(Debug, PartialEq, Eq)]
 enum EvalError {
-    /// Value we depend on is missing
-    Dependancy,
+    /// Value of this cell, which we depend on, is missing
+    Dependancy(Key),
+    /// Text used as a number
+    Type,
+    /// A name which is not defined
+    Name,
+}
+
+/// The value of a cell
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum CellValue {
+    Number(f64),
+    Text(String),
+}
+
+impl CellValue {
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            CellValue::Number(x) => Some(*x),
+            CellValue::Text(_) => None,
+        }
+    }
+
+    fn display(&self, format: NumberFormat) -> String {
+        match self {
+            CellValue::Number(x) => format.apply(*x),
+            CellValue::Text(text) => text.clone(),
+        }
+    }
+}
+
+/// Why a cell could not be evaluated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RefError {
+    /// The cell is part of a reference cycle
+    Cycle,
+    /// The cell depends on this cell, which is part of a cycle or has no value
+    Via(Key),
+    /// The formula uses text as a number
+    Type,
+    /// The formula uses a name which is not defined
+    Name,
+}
+
+/// The top-left and bottom-right corners of the rectangle with corners `a` and `b`
+fn corners(a: Key, b: Key) -> (Key, Key) {
+    let top_left = Key(a.0.min(b.0), a.1.min(b.1));
+    (top_left, Key(a.0.max(b.0), a.1.max(b.1)))
+}
+
+/// True if `key` is in the rectangle with corners `a` and `b`
+fn in_range(a: Key, b: Key, key: Key) -> bool {
+    let (a, b) = corners(a, b);
+    (a.0..=b.0).contains(&key.0) && (a.1..=b.1).contains(&key.1)
+}
+
+/// The number of cells in the rectangle with corners `a` and `b`
+fn area(a: Key, b: Key) -> u64 {
+    let (a, b) = corners(a, b);
+    u64::from(b.0 .0 - a.0 .0 + 1) * u64::from(b.1 - a.1 + 1)
+}
+
+/// The keys of the rectangle with corners `a` and `b`, column by column
+fn range_keys(a: Key, b: Key) -> impl Iterator<Item = Key> {
+    let (a, b) = corners(a, b);
+    let cols = a.0 .0..=b.0 .0;
+    cols.flat_map(move |col| (a.1..=b.1).map(move |row| Key(ColKey(col), row)))
+}
+
+/// Numbers in the rectangle with corners `a` and `b`, ordered by key
+///
+/// Cells without a number (empty or text) are skipped. Since the sheet is
+/// sparse, large ranges are found by scanning `values`.
+fn range_values(a: Key, b: Key, values: &HashMap<Key, CellValue>) -> Vec<f64> {
+    if area(a, b) <= values.len().cast() {
+        range_keys(a, b)
+            .filter_map(|key| values.get(&key)?.as_number())
+            .collect()
+    } else {
+        let mut members: Vec<_> = values
+            .iter()
+            .filter(|(key, _)| in_range(a, b, **key))
+            .collect();
+        members.sort_unstable_by_key(|(key, _)| **key);
+        members
+            .into_iter()
+            .filter_map(|(_, value)| value.as_number())
+            .collect()
+    }
+}
+
+/// How a cell displays a number
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NumberFormat {
+    /// Up to 15 significant digits, in scientific notation if very large or small
+    #[default]
+    General,
+    /// With this many decimal places
+    Fixed(u8),
+    /// Multiplied by 100, with this many decimal places and a `%` sign
+    Percent(u8),
+    /// Scientific notation with this many decimal places
+    Scientific(u8),
+}
+
+impl NumberFormat {
+    const MAX_DECIMALS: u8 = 15;
+
+    /// Split a format prefix like `[0.00]` from `input`, if present
+    fn split(input: &str) -> (Option<Self>, &str) {
+        let split = || {
+            let (spec, rest) = input.strip_prefix('[')?.split_once(']')?;
+            Some((Self::parse(spec)?, rest))
+        };
+        match split() {
+            Some((format, rest)) => (Some(format), rest),
+            None => (None, input),
+        }
+    }
+
+    /// Parse a format like `0.00`, `0%` or `0.0E`
+    fn parse(spec: &str) -> Option<Self> {
+        if spec.eq_ignore_ascii_case("general") {
+            return Some(NumberFormat::General);
+        }
+        let (spec, kind): (_, fn(u8) -> Self) = if let Some(spec) = spec.strip_suffix('%') {
+            (spec, NumberFormat::Percent)
+        } else if let Some(spec) = spec.strip_suffix(['e', 'E']) {
+            (spec, NumberFormat::Scientific)
+        } else {
+            (spec, NumberFormat::Fixed)
+        };
+        let decimals = match spec {
+            "0" => 0,
+            _ => {
+                let zeros = spec.strip_prefix("0.")?;
+                if zeros.is_empty() || zeros.bytes().any(|b| b != b'0') {
+                    return None;
+                }
+                u8::try_from(zeros.len()).ok()?
+            }
+        };
+        (decimals <= Self::MAX_DECIMALS).then(|| kind(decimals))
+    }
+
+    fn apply(self, x: f64) -> String {
+        match self {
+            _ if !x.is_finite() => x.to_string(),
+            NumberFormat::General if x == 0.0 => x.to_string(),
+            NumberFormat::General => {
+                // Rounding hides binary artifacts like 0.30000000000000004
+                let rounded: f64 = format!("{x:.14e}").parse().unwrap();
+                if (1e-6..1e15).contains(&x.abs()) {
+                    rounded.to_string()
+                } else {
+                    format!("{rounded:e}")
+                }
+            }
+            NumberFormat::Fixed(d) => format!("{x:.*}", usize::from(d)),
+            NumberFormat::Percent(d) => format!("{:.*}%", usize::from(d), x * 100.0),
+            NumberFormat::Scientific(d) => format!("{x:.*e}", usize::from(d)),
+        }
+    }
+}
+
+/// How a [`Function`] is evaluated
+#[derive(Clone, Copy, Debug)]
+enum Eval {
+    /// Over all numbers in the arguments, with ranges expanded
+    ///
+    /// Cells in a range without a number (empty or text) are skipped.
+    Aggregate(fn(&[f64]) -> f64),
+    /// Over the arguments, each evaluated as a number
+    Numbers(fn(&[f64]) -> f64),
+    /// Given the unevaluated arguments and the values of all dependencies
+    Formulas(fn(&[Formula], &HashMap<Key, CellValue>) -> Result<CellValue, EvalError>),
+}
+
+/// A built-in function, called by name from a formula
+///
+/// To add a function, add an entry to [`FUNCTIONS`].
+#[derive(Debug)]
+pub struct Function {
+    /// Upper-case name
+    name: &'static str,
+    /// Number of arguments accepted
+    args: ops::RangeInclusive<usize>,
+    eval: Eval,
+}
+
+/// Any number of arguments
+const ANY: ops::RangeInclusive<usize> = 0..=usize::MAX;
+
+static FUNCTIONS: &[Function] = &[
+    Function {
+        name: "SUM",
+        args: ANY,
+        eval: Eval::Aggregate(|x| x.iter().sum()),
+    },
+    Function {
+        name: "AVG",
+        args: ANY,
+        eval: Eval::Aggregate(|x| x.iter().sum::<f64>() / x.len() as f64),
+    },
+    Function {
+        name: "MIN",
+        args: ANY,
+        eval: Eval::Aggregate(|x| x.iter().copied().reduce(f64::min).unwrap_or(0.0)),
+    },
+    Function {
+        name: "MAX",
+        args: ANY,
+        eval: Eval::Aggregate(|x| x.iter().copied().reduce(f64::max).unwrap_or(0.0)),
+    },
+    Function {
+        name: "COUNT",
+        args: ANY,
+        eval: Eval::Aggregate(|x| x.len() as f64),
+    },
+    Function {
+        name: "ABS",
+        args: 1..=1,
+        eval: Eval::Numbers(|x| x[0].abs()),
+    },
+    Function {
+        name: "ROUND",
+        args: 1..=2,
+        eval: Eval::Numbers(|x| round(x[0], x.get(1).copied().unwrap_or(0.0))),
+    },
+    Function {
+        name: "SQRT",
+        args: 1..=1,
+        eval: Eval::Numbers(|x| x[0].sqrt()),
+    },
+    Function {
+        name: "SIN",
+        args: 1..=1,
+        eval: Eval::Numbers(|x| x[0].sin()),
+    },
+    Function {
+        name: "COS",
+        args: 1..=1,
+        eval: Eval::Numbers(|x| x[0].cos()),
+    },
+    Function {
+        name: "IF",
+        args: 3..=3,
+        // Only the chosen branch is evaluated
+        eval: Eval::Formulas(|args, values| {
+            let cond = args[0].eval_number(values)?;
+            args[if cond != 0.0 { 1 } else { 2 }].eval(values)
+        }),
+    },
+];
+
+/// Round `x` to `digits` decimal places (or, if negative, to tens, hundreds, ...)
+fn round(x: f64, digits: f64) -> f64 {
+    // More digits make no difference, but could overflow or underflow the scale
+    let digits = digits.trunc();
+    let scale = 10f64.powf(digits.clamp(f64::MIN_10_EXP.into(), f64::DIGITS.into()));
+    (x * scale).round() / scale
+}
+
+impl Function {
+    /// Find by (case-insensitive) name
+    fn find(name: &str) -> Option<&'static Self> {
+        FUNCTIONS.iter().find(|f| f.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether ranges are accepted as arguments
+    fn takes_ranges(&self) -> bool {
+        matches!(self.eval, Eval::Aggregate(_))
+    }
+
+    fn call(
+        &self,
+        args: &[Formula],
+        values: &HashMap<Key, CellValue>,
+    ) -> Result<CellValue, EvalError> {
+        match self.eval {
+            Eval::Aggregate(f) => {
+                let mut list = vec![];
+                for arg in args {
+                    if let Formula::Range(a, b) = arg {
+                        list.extend(range_values(a.key, b.key, values));
+                    } else {
+                        list.push(arg.eval_number(values)?);
+                    }
+                }
+                Ok(CellValue::Number(f(&list)))
+            }
+            Eval::Numbers(f) => {
+                let list = args.iter().map(|arg| arg.eval_number(values));
+                Ok(CellValue::Number(f(&list.collect::<Result<Vec<_>, _>>()?)))
+            }
+            Eval::Formulas(f) => f(args, values),
+        }
+    }
+}
+
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Serialize for Function {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+/// A comparison; the result is 1 if true and 0 if false
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn parse(s: &str) -> Self {
+        match s {
+            "=" => CompareOp::Eq,
+            "<>" => CompareOp::Ne,
+            "<" => CompareOp::Lt,
+            "<=" => CompareOp::Le,
+            ">" => CompareOp::Gt,
+            ">=" => CompareOp::Ge,
+            _ => unreachable!(),
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "<>",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        }
+    }
+
+    /// Compare `a` with `b`
+    ///
+    /// Text is compared ignoring case. Text never equals a number, and
+    /// ordering them is a type error.
+    fn apply(self, a: &CellValue, b: &CellValue) -> Result<bool, EvalError> {
+        use std::cmp::Ordering::*;
+        let ordering = match (a, b) {
+            (CellValue::Number(x), CellValue::Number(y)) => x.partial_cmp(y),
+            (CellValue::Text(x), CellValue::Text(y)) => {
+                Some(x.to_lowercase().cmp(&y.to_lowercase()))
+            }
+            _ if matches!(self, CompareOp::Eq | CompareOp::Ne) => None,
+            _ => return Err(EvalError::Type),
+        };
+        Ok(match self {
+            CompareOp::Eq => ordering == Some(Equal),
+            CompareOp::Ne => ordering != Some(Equal),
+            CompareOp::Lt => ordering == Some(Less),
+            CompareOp::Le => matches!(ordering, Some(Less | Equal)),
+            CompareOp::Gt => ordering == Some(Greater),
+            CompareOp::Ge => matches!(ordering, Some(Greater | Equal)),
+        })
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum Formula {
     Value(f64),
-    Reference(Key),
+    Text(String),
+    Reference(CellRef),
+    /// A defined name, replaced with a reference or range on update
+    Name(String),
+    /// Cells in a rectangle, given by two corners (only as an argument)
+    Range(CellRef, CellRef),
     /// List of values to add/subtract; if bool is true then subtract
     Summation(Vec<(Formula, bool)>),
     /// List of values to multiply/divide; if bool is true then divide
     Product(Vec<(Formula, bool)>),
+    /// A function call; only some functions accept ranges
+    Call(&'static Function, Vec<Formula>),
+    /// Values joined as text
+    Concat(Vec<Formula>),
+    /// A base raised to an exponent
+    Power(Box<Formula>, Box<Formula>),
+    Negate(Box<Formula>),
+    /// A comparison, giving 1 if true and 0 if false
+    Compare(Box<Formula>, CompareOp, Box<Formula>),
 }
 
 impl Formula {
-    fn eval(&self, values: &HashMap<Key, f64>) -> Result<f64, EvalError> {
+    /// Push referenced cells to `deps` and referenced ranges to `ranges`
+    fn dependencies(&self, deps: &mut Vec<Key>, ranges: &mut Vec<(Key, Key)>) {
+        use Formula::*;
+        match self {
+            // A name still in the formula is not defined
+            Value(_) | Text(_) | Name(_) => (),
+            Reference(r) => deps.push(r.key),
+            Range(a, b) => ranges.push(corners(a.key, b.key)),
+            Summation(v) | Product(v) => v.iter().for_each(|(f, _)| f.dependencies(deps, ranges)),
+            Call(_, args) | Concat(args) => args.iter().for_each(|f| f.dependencies(deps, ranges)),
+            Power(base, exp) => {
+                base.dependencies(deps, ranges);
+                exp.dependencies(deps, ranges);
+            }
+            Negate(f) => f.dependencies(deps, ranges),
+            Compare(a, _, b) => {
+                a.dependencies(deps, ranges);
+                b.dependencies(deps, ranges);
+            }
+        }
+    }
+
+    /// Move references by `cols` and `rows`, failing if any would leave the sheet
+    fn offset(&self, cols: i32, rows: i32) -> Option<Formula> {
+        use Formula::*;
+        let offset = |f: &Formula| f.offset(cols, rows);
+        let terms = |v: &[(Formula, bool)]| {
+            let terms = v.iter().map(|(f, op)| Some((offset(f)?, *op)));
+            terms.collect::<Option<Vec<_>>>()
+        };
+        let args = |v: &[Formula]| v.iter().map(&offset).collect::<Option<Vec<_>>>();
+        Some(match self {
+            Value(_) | Text(_) | Name(_) => self.clone(),
+            Reference(r) => Reference(r.offset(cols, rows)?),
+            Range(a, b) => Range(a.offset(cols, rows)?, b.offset(cols, rows)?),
+            Summation(v) => Summation(terms(v)?),
+            Product(v) => Product(terms(v)?),
+            Call(function, v) => Call(function, args(v)?),
+            Concat(v) => Concat(args(v)?),
+            Power(base, exp) => {
+                let base = base.offset(cols, rows)?;
+                Power(Box::new(base), Box::new(exp.offset(cols, rows)?))
+            }
+            Negate(f) => Negate(Box::new(f.offset(cols, rows)?)),
+            Compare(a, op, b) => {
+                let a = a.offset(cols, rows)?;
+                Compare(Box::new(a), *op, Box::new(b.offset(cols, rows)?))
+            }
+        })
+    }
+
+    /// Replace defined names with absolute references, returning true if any names are used
+    ///
+    /// `names` maps each name to the corners of its range; a single cell has
+    /// equal corners.
+    fn resolve(&mut self, names: &BTreeMap<String, (Key, Key)>) -> bool {
+        use Formula::*;
+        let absolute = |key| CellRef {
+            key,
+            abs_col: true,
+            abs_row: true,
+        };
+        match self {
+            Value(_) | Text(_) | Reference(_) | Range(_, _) => false,
+            Name(name) => {
+                if let Some(&(a, b)) = names.get(name.as_str()) {
+                    *self = match a == b {
+                        true => Reference(absolute(a)),
+                        false => Range(absolute(a), absolute(b)),
+                    };
+                }
+                true
+            }
+            Summation(v) | Product(v) => v
+                .iter_mut()
+                .fold(false, |used, (f, _)| f.resolve(names) | used),
+            Call(_, args) | Concat(args) => args
+                .iter_mut()
+                .fold(false, |used, f| f.resolve(names) | used),
+            Power(a, b) | Compare(a, _, b) => a.resolve(names) | b.resolve(names),
+            Negate(f) => f.resolve(names),
+        }
+    }
+
+    /// How tightly the formula binds when written, from comparisons (loosest) to values
+    fn precedence(&self) -> u8 {
+        use Formula::*;
+        match self {
+            Compare(_, _, _) => 0,
+            Concat(_) => 1,
+            Summation(_) => 2,
+            Product(_) => 3,
+            Negate(_) => 4,
+            Value(x) if x.is_sign_negative() => 4,
+            Power(_, _) => 5,
+            _ => 6,
+        }
+    }
+
+    /// Write as an expression, in parentheses if binding less tightly than `precedence`
+    fn write(&self, f: &mut fmt::Formatter, precedence: u8) -> fmt::Result {
+        use Formula::*;
+        if self.precedence() < precedence {
+            write!(f, "(")?;
+            self.write(f, 0)?;
+            return write!(f, ")");
+        }
+        match self {
+            // The parser reads too-large numbers as infinity
+            Value(x) if x.is_infinite() => write!(f, "{}1e999", if *x < 0.0 { "-" } else { "" }),
+            Value(x) => write!(f, "{x}"),
+            Text(text) => write!(f, "\"{}\"", text.replace('"', "\"\"")),
+            Reference(r) => write!(f, "{r}"),
+            Name(name) => write!(f, "{name}"),
+            Range(a, b) => write!(f, "{a}:{b}"),
+            Summation(v) => {
+                for (i, (term, sub)) in v.iter().enumerate() {
+                    match (i, sub) {
+                        (0, false) => (),
+                        (0, true) => write!(f, "-")?,
+                        (_, false) => write!(f, " + ")?,
+                        (_, true) => write!(f, " - ")?,
+                    }
+                    term.write(f, 3)?;
+                }
+                Ok(())
+            }
+            Product(v) => {
+                for (i, (factor, div)) in v.iter().enumerate() {
+                    match (i, div) {
+                        (0, false) => (),
+                        (0, true) => write!(f, "1 / ")?,
+                        (_, false) => write!(f, " * ")?,
+                        (_, true) => write!(f, " / ")?,
+                    }
+                    factor.write(f, 4)?;
+                }
+                Ok(())
+            }
+            Call(function, args) => {
+                write!(f, "{}(", function.name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    arg.write(f, 0)?;
+                }
+                write!(f, ")")
+            }
+            Concat(parts) => {
+                for (i, part) in parts.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " & ")?;
+                    }
+                    part.write(f, 2)?;
+                }
+                Ok(())
+            }
+            // `^` is right-associative and may be followed by a sign
+            Power(base, exp) => {
+                base.write(f, 6)?;
+                write!(f, " ^ ")?;
+                exp.write(f, 4)
+            }
+            Negate(x) => {
+                write!(f, "-")?;
+                x.write(f, 5)
+            }
+            // Comparisons do not chain
+            Compare(a, op, b) => {
+                a.write(f, 1)?;
+                write!(f, " {} ", op.symbol())?;
+                b.write(f, 1)
+            }
+        }
+    }
+
+    /// Evaluate, given the `values` of all dependencies
+    fn eval(&self, values: &HashMap<Key, CellValue>) -> Result<CellValue, EvalError> {
+        use Formula::*;
+        Ok(match self {
+            Text(text) => CellValue::Text(text.clone()),
+            Call(function, args) => return function.call(args, values),
+            Reference(r) => {
+                return values
+                    .get(&r.key)
+                    .cloned()
+                    .ok_or(EvalError::Dependancy(r.key))
+            }
+            Name(_) => return Err(EvalError::Name),
+            Concat(args) => {
+                let mut text = String::new();
+                for arg in args {
+                    text.push_str(&arg.eval(values)?.display(NumberFormat::General));
+                }
+                CellValue::Text(text)
+            }
+            _ => CellValue::Number(self.eval_number(values)?),
+        })
+    }
+
+    /// Evaluate as a number
+    ///
+    /// Cells in a range without a number (empty or text) are skipped.
+    fn eval_number(&self, values: &HashMap<Key, CellValue>) -> Result<f64, EvalError> {
         use Formula::*;
         Ok(match self {
             Value(x) => *x,
-            Reference(key) => return values.get(key).cloned().ok_or(EvalError::Dependancy),
+            Text(_) | Reference(_) | Name(_) | Concat(_) | Call(_, _) => {
+                return self.eval(values)?.as_number().ok_or(EvalError::Type)
+            }
+            // The grammar allows ranges only as arguments, but a name may give one
+            Range(_, _) => return Err(EvalError::Type),
             Summation(v) => {
                 let mut sum = 0.0;
                 for (f, neg) in v {
-                    let x = f.eval(values)?;
+                    let x = f.eval_number(values)?;
                     if *neg {
                         sum -= x;
                     } else {
@@ -102,7 +821,7 @@ impl Formula {
             Product(v) => {
                 let mut prod = 1.0;
                 for (f, div) in v {
-                    let x = f.eval(values)?;
+                    let x = f.eval_number(values)?;
                     if *div {
                         prod /= x;
                     } else {
@@ -111,158 +830,331 @@ impl Formula {
                 }
                 prod
             }
+
+            Power(base, exp) => base.eval_number(values)?.powf(exp.eval_number(values)?),
+            Negate(f) => -f.eval_number(values)?,
+            Compare(a, op, b) => {
+                let result = op.apply(&a.eval(values)?, &b.eval(values)?)?;
+                f64::from(u8::from(result))
+            }
         })
     }
 }
 
+/// Writes the formula in a form which parses back to the same formula
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
 mod parser {
-    use super::{ColKey, Formula, Key};
-    use pest::error::Error;
-    use pest::iterators::Pairs;
-    use pest::Parser;
+    use super::{CellRef, CompareOp, Formula, Function};
+    use pest::error::{Error, ErrorVariant};
+    use pest::iterators::{Pair, Pairs};
+    use pest::{Parser, Position, Span};
     use pest_derive::Parser;
 
+    /// Maximum nesting of parentheses, limiting recursion
+    const MAX_DEPTH: usize = 32;
+
     #[derive(Parser)]
     #[grammar = "cells.pest"]
     pub struct FormulaParser;
 
-    fn parse_value(mut pairs: Pairs<'_, Rule>) -> Formula {
+    fn error(span: Span<'_>, message: String) -> Error<Rule> {
+        Error::new_from_span(ErrorVariant::CustomError { message }, span)
+    }
+
+    fn parse_reference(pair: Pair<'_, Rule>) -> Result<CellRef, Error<Rule>> {
+        let s = pair.as_str();
+        CellRef::parse(s).ok_or_else(|| error(pair.as_span(), format!("no cell {s}")))
+    }
+
+    fn parse_value(mut pairs: Pairs<'_, Rule>) -> Result<Formula, Error<Rule>> {
         let pair = pairs.next().unwrap();
         assert!(pairs.next().is_none());
         match pair.as_rule() {
-            Rule::number => Formula::Value(pair.as_span().as_str().parse().unwrap()),
-            Rule::reference => {
-                let s = pair.as_span().as_str();
-                assert!(s.len() >= 2);
-                let mut col = s.as_bytes()[0];
-                if col > b'Z' {
-                    col -= b'a' - b'A';
-                }
-                let col = ColKey::from_u8(col);
-                let row = s[1..].parse().unwrap();
-                let key = Key(col, row);
-                Formula::Reference(key)
+            Rule::number => match pair.as_str().parse() {
+                Ok(value) => Ok(Formula::Value(value)),
+                Err(err) => Err(error(pair.as_span(), format!("bad number: {err}"))),
+            },
+            Rule::string => {
+                let text = pair.into_inner().next().unwrap().as_str();
+                Ok(Formula::Text(text.replace("\"\"", "\"")))
             }
-            Rule::expression => parse_expression(pair.into_inner()),
+            Rule::reference => parse_reference(pair).map(Formula::Reference),
+            Rule::name => Ok(Formula::Name(pair.as_str().to_string())),
+            Rule::function => parse_function(pair),
+            Rule::expression => parse_expression(pair),
             _ => unreachable!(),
         }
     }
 
-    fn parse_product(pairs: Pairs<'_, Rule>) -> Formula {
+    fn parse_function(pair: Pair<'_, Rule>) -> Result<Formula, Error<Rule>> {
+        let span = pair.as_span();
+        let mut pairs = pair.into_inner();
+        let name = pairs.next().unwrap();
+        let Some(function) = Function::find(name.as_str()) else {
+            let message = format!("unknown function {}", name.as_str());
+            return Err(error(name.as_span(), message));
+        };
+        let mut args = vec![];
+        for pair in pairs {
+            args.push(match pair.as_rule() {
+                Rule::range if !function.takes_ranges() => {
+                    let message = format!("{} does not take a range", function.name);
+                    return Err(error(pair.as_span(), message));
+                }
+                Rule::range => {
+                    let mut keys = pair.into_inner();
+                    let a = parse_reference(keys.next().unwrap())?;
+                    let b = parse_reference(keys.next().unwrap())?;
+                    Formula::Range(a, b)
+                }
+                Rule::expression => parse_expression(pair)?,
+                _ => unreachable!(),
+            });
+        }
+        if !function.args.contains(&args.len()) {
+            let (min, max) = (function.args.start(), function.args.end());
+            let expected = match max - min {
+                0 => min.to_string(),
+                1 => format!("{min} or {max}"),
+                _ => format!("{min} to {max}"),
+            };
+            let message = format!("{} takes {expected} arguments", function.name);
+            return Err(error(span, message));
+        }
+        Ok(Formula::Call(function, args))
+    }
+
+    fn parse_factor(pair: Pair<'_, Rule>) -> Result<Formula, Error<Rule>> {
+        let mut pairs = pair.into_inner();
+        let mut pair = pairs.next().unwrap();
+        let negate = pair.as_rule() == Rule::neg;
+        if negate {
+            pair = pairs.next().unwrap();
+        }
+        let power = parse_power(pair)?;
+        Ok(match negate {
+            false => power,
+            true => Formula::Negate(Box::new(power)),
+        })
+    }
+
+    /// Parse a power; `^` is right-associative
+    fn parse_power(pair: Pair<'_, Rule>) -> Result<Formula, Error<Rule>> {
+        let mut pairs = pair.into_inner();
+        let base = parse_value(pairs.next().unwrap().into_inner())?;
+        match pairs.next() {
+            Some(exp) => Ok(Formula::Power(Box::new(base), Box::new(parse_factor(exp)?))),
+            None => Ok(base),
+        }
+    }
+
+    fn parse_product(pairs: Pairs<'_, Rule>) -> Result<Formula, Error<Rule>> {
         let mut product = vec![];
         let mut div = false;
         for pair in pairs {
             match pair.as_rule() {
-                Rule::product_op => {
-                    div = match pair.as_span().as_str() {
-                        "*" => false,
-                        "/" => true,
-                        other => panic!("expected `*` or `/`, found `{other}`"),
-                    };
-                }
-                Rule::value => {
-                    let formula = parse_value(pair.into_inner());
+                Rule::product_op => div = pair.as_str() == "/",
+                Rule::factor => {
+                    let formula = parse_factor(pair)?;
                     product.push((formula, div));
                     div = false;
                 }
                 _ => unreachable!(),
             }
         }
-        debug_assert!(!div);
-        if product.len() == 1 {
-            debug_assert!(!product[0].1);
-            product.pop().unwrap().0
+        if product.len() == 1 && !product[0].1 {
+            Ok(product.pop().unwrap().0)
         } else {
-            debug_assert!(product.len() > 1);
-            Formula::Product(product)
+            Ok(Formula::Product(product))
         }
     }
 
-    fn parse_summation(pairs: Pairs<'_, Rule>) -> Formula {
+    fn parse_summation(pairs: Pairs<'_, Rule>) -> Result<Formula, Error<Rule>> {
         let mut summation = vec![];
         let mut sub = false;
         for pair in pairs {
             match pair.as_rule() {
-                Rule::sum_op => {
-                    sub = match pair.as_span().as_str() {
-                        "+" => false,
-                        "-" => true,
-                        other => panic!("expected `+` or `-`, found `{other}`"),
-                    };
-                }
+                Rule::sum_op => sub = pair.as_str() == "-",
                 Rule::product => {
-                    let formula = parse_product(pair.into_inner());
+                    let formula = parse_product(pair.into_inner())?;
                     summation.push((formula, sub));
                     sub = false;
                 }
                 _ => unreachable!(),
             }
         }
-        debug_assert!(!sub);
+        // A leading `-` gives a single, negated term
         if summation.len() == 1 && !summation[0].1 {
-            summation.pop().unwrap().0
+            Ok(summation.pop().unwrap().0)
         } else {
-            debug_assert!(summation.len() > 1);
-            Formula::Summation(summation)
+            Ok(Formula::Summation(summation))
         }
     }
 
-    fn parse_expression(mut pairs: Pairs<'_, Rule>) -> Formula {
-        let pair = pairs.next().unwrap();
-        if let Some(pair) = pairs.next() {
-            if pair.as_rule() != Rule::EOI {
-                panic!("unexpected next pair: {pair:?}");
-            }
+    fn parse_concatenation(pairs: Pairs<'_, Rule>) -> Result<Formula, Error<Rule>> {
+        let mut parts = vec![];
+        for pair in pairs {
+            assert_eq!(pair.as_rule(), Rule::summation);
+            parts.push(parse_summation(pair.into_inner())?);
+        }
+        if parts.len() == 1 {
+            Ok(parts.pop().unwrap())
+        } else {
+            Ok(Formula::Concat(parts))
         }
+    }
+
+    fn parse_comparison(mut pairs: Pairs<'_, Rule>) -> Result<Formula, Error<Rule>> {
+        let a = parse_concatenation(pairs.next().unwrap().into_inner())?;
+        let Some(op) = pairs.next() else {
+            return Ok(a);
+        };
+        let op = CompareOp::parse(op.as_str());
+        let b = parse_concatenation(pairs.next().unwrap().into_inner())?;
+        Ok(Formula::Compare(Box::new(a), op, Box::new(b)))
+    }
+
+    fn parse_expression(pair: Pair<'_, Rule>) -> Result<Formula, Error<Rule>> {
         assert_eq!(pair.as_rule(), Rule::expression);
         let mut pairs = pair.into_inner();
 
         let pair = pairs.next().unwrap();
         assert!(pairs.next().is_none());
-        assert_eq!(pair.as_rule(), Rule::summation);
-        parse_summation(pair.into_inner())
+        assert_eq!(pair.as_rule(), Rule::comparison);
+        parse_comparison(pair.into_inner())
     }
 
-    pub fn parse(source: &str) -> Result<Option<Formula>, Error<Rule>> {
-        FormulaParser::parse(Rule::cell, source).map(|mut pairs| {
-            let pair = pairs.next().unwrap();
-            match pair.as_rule() {
-                Rule::formula => Some(parse_expression(pair.into_inner())),
-                Rule::text => None,
-                _ => unreachable!(),
+    /// Reject input nested too deeply to parse without risking stack overflow
+    ///
+    /// Each `^` also counts as nesting, since powers are right-associative.
+    fn check_depth(source: &str) -> Result<(), Error<Rule>> {
+        let (mut depth, mut powers) = (0usize, 0usize);
+        let mut in_text = false;
+        for (i, c) in source.char_indices() {
+            match c {
+                '"' => in_text = !in_text,
+                _ if in_text => continue,
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                '^' => powers += 1,
+                _ => continue,
             }
-        })
+            if depth + powers > MAX_DEPTH {
+                let message = format!("more than {MAX_DEPTH} nested parentheses or powers");
+                let pos = Position::new(source, i).unwrap();
+                return Err(Error::new_from_pos(
+                    ErrorVariant::CustomError { message },
+                    pos,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// True if `s` may be used as a name: it must not look like a reference
+    pub fn is_name(s: &str) -> bool {
+        let pair = FormulaParser::parse(Rule::name, s)
+            .ok()
+            .and_then(|mut p| p.next());
+        pair.is_some_and(|pair| pair.as_str().len() == s.len())
+    }
+
+    pub fn parse(source: &str) -> Result<Option<Formula>, Error<Rule>> {
+        if source.starts_with('=') {
+            check_depth(source)?;
+        }
+        let pair = FormulaParser::parse(Rule::cell, source)?.next().unwrap();
+        match pair.as_rule() {
+            // The formula is followed by EOI
+            Rule::formula => parse_expression(pair.into_inner().next().unwrap()).map(Some),
+            Rule::text => Ok(None),
+            _ => unreachable!(),
+        }
     }
 }
 
-#[derive(Debug, Default)]
+/// Selections larger than this many cells are not highlighted
+const MAX_HIGHLIGHTED: u64 = 10_000;
+
+/// Levels of at least this many cells are evaluated in parallel
+///
+/// Smaller levels are cheaper to evaluate than to distribute over threads.
+#[cfg(feature = "parallel")]
+const PARALLEL_LEVEL: usize = 256;
+
+/// A cell; only the input is (de)serialized
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
 struct Cell {
     input: String,
+    #[serde(skip)]
     formula: Option<Formula>,
-    parse_error: bool,
+    /// Cells referenced by `formula`, sorted and without duplicates
+    #[serde(skip)]
+    deps: Vec<Key>,
+    /// Ranges referenced by `formula`, as top-left and bottom-right corners
+    #[serde(skip)]
+    ranges: Vec<(Key, Key)>,
+    /// True if `formula` uses names, defined or not
+    #[serde(skip)]
+    named: bool,
+    /// Format given as a prefix of `input`, if any
+    #[serde(skip)]
+    format: Option<NumberFormat>,
+    /// Description of the parse error, if any
+    #[serde(skip)]
+    parse_error: Option<String>,
+    #[serde(skip)]
+    ref_error: Option<RefError>,
+    #[serde(skip)]
     display: String,
+    /// Free text attached by the user; empty if none
+    #[serde(skip)]
+    note: String,
 }
 
 impl Cell {
-    fn new<T: ToString>(input: T) -> Self {
-        let mut cell = Cell::default();
-        cell.update(input.to_string());
-        cell
-    }
-
-    fn update(&mut self, input: String) {
-        match parser::parse(&input) {
+    /// Set the input, returning any parse error
+    ///
+    /// Names defined in `names` are resolved; see [`CellData::names`].
+    fn update(
+        &mut self,
+        input: String,
+        names: &BTreeMap<String, (Key, Key)>,
+    ) -> Result<(), pest::error::Error<parser::Rule>> {
+        let (format, body) = NumberFormat::split(&input);
+        self.format = format;
+        let result = match parser::parse(body) {
             Ok(opt_formula) => {
                 self.formula = opt_formula;
-                self.parse_error = false;
+                self.parse_error = None;
+                self.display.clear();
+                Ok(())
             }
             Err(error) => {
-                println!("Parse error: {error}");
+                self.formula = None;
                 self.display = "BAD FORMULA".to_string();
-                self.parse_error = true;
+                self.parse_error = Some(format!("parse error: {}", error.variant.message()));
+                Err(error)
             }
-        }
+        };
         self.input = input;
+
+        self.deps.clear();
+        self.ranges.clear();
+        self.named = false;
+        if let Some(formula) = self.formula.as_mut() {
+            self.named = formula.resolve(names);
+            formula.dependencies(&mut self.deps, &mut self.ranges);
+            self.deps.sort_unstable();
+            self.deps.dedup();
+        }
+        result
     }
 
     /// Get display string
@@ -274,75 +1166,914 @@ impl Cell {
         }
     }
 
-    fn try_eval(&mut self, values: &HashMap<Key, f64>) -> Result<Option<f64>, EvalError> {
-        if self.parse_error {
+    fn try_eval(
+        &mut self,
+        values: &HashMap<Key, CellValue>,
+    ) -> Result<Option<CellValue>, EvalError> {
+        self.ref_error = None;
+        if self.parse_error.is_some() {
             // Display the error locally; propegate NaN
-            Ok(Some(f64::NAN))
+            Ok(Some(CellValue::Number(f64::NAN)))
         } else if let Some(ref f) = self.formula {
             let value = f.eval(values)?;
-            self.display = value.to_string();
+            self.display = value.display(self.format.unwrap_or_default());
             Ok(Some(value))
         } else {
-            Ok(self.input.parse().ok())
+            let (format, text) = NumberFormat::split(&self.input);
+            let value = match text.parse() {
+                Ok(x) => Some(CellValue::Number(x)),
+                Err(_) if text.is_empty() => None,
+                Err(_) => Some(CellValue::Text(text.to_string())),
+            };
+            if let (Some(format), Some(value)) = (format, value.as_ref()) {
+                // Display the value without the format prefix
+                self.display = value.display(format);
+            }
+            Ok(value)
+        }
+    }
+
+    /// True if the formula references `key`, directly or within a range
+    fn depends_on(&self, key: Key) -> bool {
+        self.deps.binary_search(&key).is_ok()
+            || self.ranges.iter().any(|(a, b)| in_range(*a, *b, key))
+    }
+
+    /// The cells in `nodes` which the formula references
+    fn deps_within(&self, nodes: &HashSet<Key>) -> Vec<Key> {
+        if self.ranges.is_empty() {
+            let deps = self.deps.iter().filter(|key| nodes.contains(key));
+            deps.copied().collect()
+        } else {
+            let deps = nodes.iter().filter(|key| self.depends_on(**key));
+            deps.copied().collect()
+        }
+    }
+
+    fn set_ref_error(&mut self, error: RefError) {
+        self.display = match error {
+            RefError::Cycle => "#CYCLE!",
+            RefError::Via(_) => "#REF!",
+            RefError::Type => "#VALUE!",
+            RefError::Name => "#NAME?",
         }
+        .to_string();
+        self.ref_error = Some(error);
     }
 }
 
-#[derive(Debug)]
-struct CellData {
-    cells: HashMap<Key, Cell>,
-    values: HashMap<Key, f64>,
+/// Tarjan's algorithm for strongly connected components
+struct Components<'a> {
+    cells: &'a HashMap<Key, Cell>,
+    nodes: &'a HashSet<Key>,
+    /// Index and low-link of visited nodes
+    index: HashMap<Key, (usize, usize)>,
+    stack: Vec<Key>,
+    on_stack: HashSet<Key>,
+    components: Vec<Vec<Key>>,
 }
 
-impl CellData {
-    fn new() -> Self {
-        CellData {
-            cells: HashMap::new(),
-            values: HashMap::new(),
+impl<'a> Components<'a> {
+    /// Find components of the graph of `nodes`, with edges from cells to dependencies
+    ///
+    /// Edges leaving `nodes` are ignored. Each component is listed after
+    /// all components it depends on.
+    fn find(cells: &'a HashMap<Key, Cell>, nodes: &'a HashSet<Key>) -> Vec<Vec<Key>> {
+        let mut this = Components {
+            cells,
+            nodes,
+            index: HashMap::new(),
+            stack: vec![],
+            on_stack: HashSet::new(),
+            components: vec![],
+        };
+        for key in nodes.iter().copied() {
+            if !this.index.contains_key(&key) {
+                this.visit(key);
+            }
         }
+        this.components
     }
-    fn update_values(&mut self) {
-        // NOTE: this is a fairly naive algorithm, but correct!
-        self.values.clear();
 
-        let mut waiting = vec![];
-        for (key, cell) in self.cells.iter_mut() {
-            match cell.try_eval(&self.values) {
-                Ok(Some(value)) => {
-                    self.values.insert(*key, value);
+    fn visit(&mut self, key: Key) {
+        let index = self.index.len();
+        self.index.insert(key, (index, index));
+        self.stack.push(key);
+        self.on_stack.insert(key);
+
+        let deps = self
+            .cells
+            .get(&key)
+            .map(|cell| cell.deps_within(self.nodes));
+        for dep in deps.unwrap_or_default() {
+            let low = if !self.index.contains_key(&dep) {
+                self.visit(dep);
+                self.index[&dep].1
+            } else if self.on_stack.contains(&dep) {
+                self.index[&dep].0
+            } else {
+                continue;
+            };
+            let entry = self.index.get_mut(&key).unwrap();
+            entry.1 = entry.1.min(low);
+        }
+
+        let (index, low) = self.index[&key];
+        if index == low {
+            let mut component = vec![];
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(&member);
+                component.push(member);
+                if member == key {
+                    break;
                 }
-                Ok(None) => (),
-                Err(EvalError::Dependancy) => waiting.push(*key),
             }
+            self.components.push(component);
         }
+    }
+}
 
-        let mut remaining = waiting.len();
-        let mut queue = vec![];
-
-        while remaining > 0 {
-            std::mem::swap(&mut waiting, &mut queue);
-            for key in queue.drain(..) {
-                let cell = self.cells.get_mut(&key).unwrap();
-                match cell.try_eval(&self.values) {
+/// Contents of a sheet: cell inputs and their values
+///
+/// This may be used without a UI: values are recomputed on each edit.
+#[derive(Debug, Default, Serialize)]
+pub struct CellData {
+    cells: HashMap<Key, Cell>,
+    values: HashMap<Key, CellValue>,
+    /// For each cell, the cells whose formulas reference it (not via ranges)
+    #[serde(skip)]
+    dependents: HashMap<Key, HashSet<Key>>,
+    /// Cells whose formulas reference ranges
+    #[serde(skip)]
+    ranged: HashSet<Key>,
+    /// Defined names, each with the corners of its range
+    names: BTreeMap<String, (Key, Key)>,
+    /// Cells whose formulas use names
+    #[serde(skip)]
+    named: HashSet<Key>,
+}
+
+impl CellData {
+    pub fn new() -> Self {
+        CellData {
+            cells: HashMap::new(),
+            values: HashMap::new(),
+            dependents: HashMap::new(),
+            ranged: HashSet::new(),
+            names: BTreeMap::new(),
+            named: HashSet::new(),
+        }
+    }
+
+    /// All cells from A1 to the last used row and column
+    ///
+    /// Gives displayed values, or inputs if `inputs`.
+    fn used_rows(&self, inputs: bool) -> Vec<Vec<String>> {
+        let Some((_, Key(last_col, last_row))) = self.used_range() else {
+            return vec![];
+        };
+        (1..=last_row)
+            .map(|row| {
+                (0..=last_col.0)
+                    .map(|col| {
+                        let cell = self.cells.get(&Key(ColKey(col), row));
+                        let text = |cell: &Cell| match inputs {
+                            false => cell.display(),
+                            true => cell.input.clone(),
+                        };
+                        cell.map(text).unwrap_or_default()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Inputs of the rectangle with corners `a` and `b`, row by row
+    ///
+    /// Rows and columns beyond the last used are omitted.
+    pub fn range_inputs(&self, a: Key, b: Key) -> Vec<Vec<String>> {
+        let Some((_, last)) = self.used_range() else {
+            return vec![];
+        };
+        let (a, b) = corners(a, b);
+        let b = Key(b.0.min(last.0), b.1.min(last.1));
+        (a.1..=b.1)
+            .map(|row| {
+                (a.0 .0..=b.0 .0)
+                    .map(|col| {
+                        let cell = self.cells.get(&Key(ColKey(col), row));
+                        cell.map(|cell| cell.input.clone()).unwrap_or_default()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The rectangle from A1 to the last used column and row, if any cell is used
+    fn used_range(&self) -> Option<(Key, Key)> {
+        let used = self.cells.iter().filter(|(_, cell)| !cell.input.is_empty());
+        let (mut last_col, mut last_row) = (None, 0);
+        for (Key(col, row), _) in used {
+            last_col = last_col.max(Some(*col));
+            last_row = last_row.max(*row);
+        }
+        Some((Key(ColKey(0), 1), Key(last_col?, last_row)))
+    }
+
+    /// Sort the rows of the rectangle with corners `a` and `b` by the values in column `col`
+    ///
+    /// See [`sort_order`]. References in moved formulas are adjusted as when
+    /// pasting. Returns the replaced inputs, for undo.
+    fn sort_rows(&mut self, a: Key, b: Key, col: ColKey, descending: bool) -> Vec<(Key, String)> {
+        let Some((_, last)) = self.used_range() else {
+            return vec![];
+        };
+        // Only the used part of the rectangle needs sorting
+        let (a, b) = corners(a, b);
+        let b = Key(b.0.min(last.0), b.1.min(last.1));
+        if a.0 > b.0 || a.1 > b.1 {
+            return vec![];
+        }
+
+        let mut rows: Vec<u32> = (a.1..=b.1).collect();
+        rows.sort_by(|x, y| {
+            let value = |row| self.values.get(&Key(col, row));
+            sort_order(value(*x), value(*y), descending)
+        });
+        let mut inputs = vec![];
+        for (row, from) in (a.1..).zip(rows) {
+            if row == from {
+                continue;
+            }
+            for col in a.0 .0..=b.0 .0 {
+                let cell = self.cells.get(&Key(ColKey(col), from));
+                let input = cell.map(|cell| cell.input.as_str()).unwrap_or_default();
+                let moved = offset_input(input, 0, row as i32 - from as i32);
+                inputs.push((
+                    Key(ColKey(col), row),
+                    moved.unwrap_or_else(|| input.to_string()),
+                ));
+            }
+        }
+        self.replace(inputs)
+    }
+
+    /// The next cell after `from` in reading order, wrapping around, whose input
+    /// or displayed text contains `query` (ignoring ASCII case)
+    fn find(&self, query: &str, from: Option<Key>) -> Option<Key> {
+        let query = query.to_ascii_lowercase();
+        let contains = |text: &str| text.to_ascii_lowercase().contains(&query);
+        let found = self.cells.iter().filter(|(_, cell)| {
+            !cell.input.is_empty() && (contains(&cell.input) || contains(&cell.display()))
+        });
+        let mut found: Vec<Key> = found.map(|(key, _)| *key).collect();
+        let reading_order = |key: &Key| (key.1, key.0);
+        found.sort_unstable_by_key(reading_order);
+        let from = from.as_ref().map(reading_order);
+        let next = found.iter().find(|key| Some(reading_order(*key)) > from);
+        next.or(found.first()).copied()
+    }
+
+    /// Replace `query` (ignoring ASCII case) with `with` in all inputs
+    ///
+    /// Returns the replaced inputs, for undo.
+    fn replace_all(&mut self, query: &str, with: &str) -> Vec<(Key, String)> {
+        let inputs = self
+            .cells
+            .iter()
+            .filter_map(|(key, cell)| Some((*key, replace_ignore_case(&cell.input, query, with)?)));
+        let inputs = inputs.collect();
+        self.replace(inputs)
+    }
+
+    /// The inputs of all used cells
+    fn inputs(&self) -> BTreeMap<Key, String> {
+        let used = self.cells.iter().filter(|(_, cell)| !cell.input.is_empty());
+        used.map(|(key, cell)| (*key, cell.input.clone())).collect()
+    }
+
+    /// The notes of all cells having one
+    fn notes(&self) -> BTreeMap<Key, String> {
+        let noted = self.cells.iter().filter(|(_, cell)| !cell.note.is_empty());
+        noted.map(|(key, cell)| (*key, cell.note.clone())).collect()
+    }
+
+    /// Attach `note` to cell `key`, replacing any note; an empty note removes it
+    fn set_note(&mut self, key: Key, note: String) {
+        if !note.is_empty() || self.cells.contains_key(&key) {
+            self.cells.entry(key).or_default().note = note;
+        }
+    }
+
+    /// Construct from defined names (as [`range_text`]) and the inputs of used cells
+    ///
+    /// Names which are invalid or do not give a range are skipped.
+    fn from_inputs(names: BTreeMap<String, String>, inputs: BTreeMap<Key, String>) -> Self {
+        let mut data = CellData::new();
+        for (name, range) in names {
+            if let Some(range) = parse_range(&range).filter(|_| parser::is_name(&name)) {
+                data.names.insert(name, range);
+            }
+        }
+        let keys: Vec<Key> = inputs.keys().copied().collect();
+        for (key, input) in inputs {
+            let _ = data.update_cell(key, input);
+        }
+        data.update_values(keys);
+        data
+    }
+
+    /// Write used cells to a CSV file, returning the number of rows
+    ///
+    /// Writes displayed values, or inputs (including formulas) if `inputs`.
+    fn write_csv(&self, path: &Path, inputs: bool) -> Result<usize, csv::Error> {
+        let rows = self.used_rows(inputs);
+        let mut writer = csv::WriterBuilder::new().flexible(true).from_path(path)?;
+        for row in &rows {
+            writer.write_record(row)?;
+        }
+        writer.flush()?;
+        Ok(rows.len())
+    }
+
+    /// Write used cells to a standalone HTML file as a table, returning the number of rows
+    ///
+    /// Cells show displayed values; if `inputs`, cells whose input differs
+    /// (such as formulas) give it as a `title`, shown as a tooltip.
+    fn write_html(&self, path: &Path, title: &str, inputs: bool) -> io::Result<usize> {
+        let values = self.used_rows(false);
+        let sources = self.used_rows(true);
+        let title = escape_html(title);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n{HTML_STYLE}</head>\n<body>\n\
+             <table>\n<caption>{title}</caption>\n<tr><th></th>"
+        );
+        let cols = values.first().map(|row| row.len()).unwrap_or_default();
+        for col in ColKey::iter_keys().take(cols) {
+            html += &format!("<th>{col}</th>");
+        }
+        html += "</tr>\n";
+        for (row, (shown, entered)) in (1..).zip(values.iter().zip(&sources)) {
+            html += &format!("<tr><th>{row}</th>");
+            for (value, input) in shown.iter().zip(entered) {
+                let td = match inputs && input != value {
+                    true => format!("<td title=\"{}\">", escape_html(input)),
+                    false => "<td>".to_string(),
+                };
+                html += &format!("{td}{}</td>", escape_html(value));
+            }
+            html += "</tr>\n";
+        }
+        html += "</table>\n</body>\n</html>\n";
+        std::fs::write(path, html)?;
+        Ok(values.len())
+    }
+
+    /// Replace all cells with those read from a CSV file, starting at A1
+    ///
+    /// Returns the number of rows and of parse errors.
+    fn read_csv(&mut self, path: &Path) -> Result<(usize, usize), csv::Error> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_path(path)?;
+        let mut rows = vec![];
+        for record in reader.records() {
+            rows.push(record?.iter().map(str::to_string).collect());
+        }
+        let n = rows.len();
+        let names = std::mem::take(&mut self.names);
+        *self = CellData::new();
+        self.names = names;
+        Ok((n, self.paste(Key(ColKey(0), 1), rows)))
+    }
+
+    /// The displayed value of cell `key`
+    pub fn display(&self, key: Key) -> String {
+        let cell = self.cells.get(&key);
+        cell.map(|cell| cell.display()).unwrap_or_default()
+    }
+
+    /// Set the input of cell `key`, then update values
+    pub fn set_input(
+        &mut self,
+        key: Key,
+        input: String,
+    ) -> Result<(), pest::error::Error<parser::Rule>> {
+        let result = self.update_cell(key, input);
+        self.update_values([key]);
+        result
+    }
+
+    /// Set the inputs of several cells, then update values
+    ///
+    /// Returns the replaced inputs, for undo.
+    fn replace(&mut self, inputs: Vec<(Key, String)>) -> Vec<(Key, String)> {
+        let mut prev = Vec::with_capacity(inputs.len());
+        for (key, input) in inputs {
+            let cell = self.cells.get(&key);
+            prev.push((key, cell.map(|cell| cell.input.clone()).unwrap_or_default()));
+            let _ = self.update_cell(key, input);
+        }
+        self.update_values(prev.iter().map(|(key, _)| *key));
+        prev
+    }
+
+    /// Clear all used cells in the rectangle with corners `a` and `b`
+    ///
+    /// Returns the replaced inputs, for undo.
+    fn clear(&mut self, a: Key, b: Key) -> Vec<(Key, String)> {
+        let used = self
+            .cells
+            .iter()
+            .filter(|(key, cell)| !cell.input.is_empty() && in_range(a, b, **key));
+        let inputs = used.map(|(key, _)| (*key, String::new())).collect();
+        self.replace(inputs)
+    }
+
+    /// Set inputs from `rows`, with the first at `origin`
+    ///
+    /// Fields beyond the sheet are ignored. Returns the number of parse errors.
+    fn paste(&mut self, origin: Key, rows: Vec<Vec<String>>) -> usize {
+        let mut errors = 0;
+        let mut changed = vec![];
+        for (row, fields) in (origin.1..=MAX_ROW).zip(rows) {
+            let cols = (origin.0 .0..).map_while(ColKey::try_from_index);
+            for (col, input) in cols.zip(fields) {
+                let key = Key(col, row);
+                errors += self.update_cell(key, input).is_err() as usize;
+                changed.push(key);
+            }
+        }
+        self.update_values(changed);
+        errors
+    }
+
+    /// Set the input of cell `key` without evaluating
+    ///
+    /// Call [`Self::update_values`] afterwards.
+    fn update_cell(
+        &mut self,
+        key: Key,
+        input: String,
+    ) -> Result<(), pest::error::Error<parser::Rule>> {
+        let cell = self.cells.entry(key).or_default();
+        for dep in &cell.deps {
+            if let Some(dependents) = self.dependents.get_mut(dep) {
+                dependents.remove(&key);
+            }
+        }
+        let result = cell.update(input, &self.names);
+        for dep in &cell.deps {
+            self.dependents.entry(*dep).or_default().insert(key);
+        }
+        if cell.ranges.is_empty() {
+            self.ranged.remove(&key);
+        } else {
+            self.ranged.insert(key);
+        }
+        if cell.named {
+            self.named.insert(key);
+        } else {
+            self.named.remove(&key);
+        }
+        result
+    }
+
+    /// Define `name` as the rectangle with corners `a` and `b`, or remove it if `None`
+    ///
+    /// Cells using names are updated, then re-evaluated with their dependents.
+    fn set_name(&mut self, name: String, range: Option<(Key, Key)>) {
+        match range {
+            Some((a, b)) => self.names.insert(name, corners(a, b)),
+            None => self.names.remove(&name),
+        };
+        let named: Vec<Key> = self.named.iter().copied().collect();
+        for key in &named {
+            let input = self.cells[key].input.clone();
+            let _ = self.update_cell(*key, input);
+        }
+        self.update_values(named);
+    }
+
+    /// Defined names, as shown to the user
+    fn names_text(&self) -> String {
+        if self.names.is_empty() {
+            return "No names defined".to_string();
+        }
+        let names = self
+            .names
+            .iter()
+            .map(|(name, (a, b))| format!("{name} = {}", range_text(*a, *b)));
+        format!("Names: {}", names.collect::<Vec<_>>().join(", "))
+    }
+
+    /// The cells whose formulas reference `key`, directly or within a range
+    fn dependents_of(&self, key: Key) -> HashSet<Key> {
+        let mut dependents = self.dependents.get(&key).cloned().unwrap_or_default();
+        let ranged = self.ranged.iter().filter(|other| {
+            let ranges = &self.cells[other].ranges;
+            ranges.iter().any(|(a, b)| in_range(*a, *b, key))
+        });
+        dependents.extend(ranged);
+        dependents
+    }
+
+    /// The cells which cell `key` references and, if `dependents`, which reference it
+    ///
+    /// Referenced ranges are included while the total is at most
+    /// [`MAX_HIGHLIGHTED`] cells.
+    fn traced(&self, key: Key, dependents: bool) -> Vec<Key> {
+        let mut traced = vec![];
+        if let Some(cell) = self.cells.get(&key) {
+            traced.extend_from_slice(&cell.deps);
+            for (a, b) in cell.ranges.iter().copied() {
+                if traced.len() as u64 + area(a, b) <= MAX_HIGHLIGHTED {
+                    traced.extend(range_keys(a, b));
+                }
+            }
+        }
+        if dependents {
+            traced.extend(self.dependents_of(key));
+        }
+        traced
+    }
+
+    /// Status text for cell `key`: its value or an explanation of its error
+    fn status(&self, key: Key) -> String {
+        let Some(cell) = self.cells.get(&key) else {
+            return key.to_string();
+        };
+        let status = if let Some(message) = cell.parse_error.as_ref() {
+            format!("{key}: {message}")
+        } else {
+            let value = || format!("{key}: {}", cell.display());
+            self.describe_error(key).unwrap_or_else(value)
+        };
+        match cell.note.is_empty() {
+            true => status,
+            false => format!("{status} | Note: {}", cell.note),
+        }
+    }
+
+    /// Explain why cell `key` could not be evaluated, if so
+    fn describe_error(&self, key: Key) -> Option<String> {
+        let ref_error = |key| self.cells.get(&key).and_then(|cell| cell.ref_error);
+        Some(match ref_error(key)? {
+            RefError::Cycle => format!("{key} is part of a reference cycle"),
+            RefError::Type => format!("{key} uses text as a number"),
+            RefError::Name => format!("{key} uses an undefined name"),
+            RefError::Via(other) => match ref_error(other) {
+                Some(RefError::Cycle) => {
+                    format!("{key} depends on {other}, which is part of a reference cycle")
+                }
+                Some(RefError::Type) => {
+                    format!("{key} depends on {other}, which uses text as a number")
+                }
+                Some(RefError::Name) => {
+                    format!("{key} depends on {other}, which uses an undefined name")
+                }
+                _ => format!("{key} depends on {other}, which has no value"),
+            },
+        })
+    }
+
+    /// Re-evaluate the `changed` cells and all cells depending on them
+    ///
+    /// Cells are evaluated in dependency order, each once, level by level:
+    /// each level holds the cells whose dependencies have all been evaluated,
+    /// so its cells do not depend on each other. Cells in a cycle show
+    /// `#CYCLE!`; cells depending on a cycle or a cell without value show
+    /// `#REF!`.
+    fn update_values(&mut self, changed: impl IntoIterator<Item = Key>) {
+        let mut affected = HashSet::new();
+        let mut stack: Vec<Key> = changed.into_iter().collect();
+        while let Some(key) = stack.pop() {
+            if affected.insert(key) {
+                stack.extend(self.dependents_of(key));
+            }
+        }
+        let _span = tracing::debug_span!("cells::update_values", cells = affected.len()).entered();
+
+        // For each affected cell, the number of its dependencies not yet evaluated
+        let mut blockers: HashMap<Key, usize> = HashMap::new();
+        let mut ready = vec![];
+        for key in affected.iter().copied() {
+            self.values.remove(&key);
+            let deps = self.cells.get(&key).map(|cell| cell.deps_within(&affected));
+            match deps.unwrap_or_default().len() {
+                0 => ready.push(key),
+                n => {
+                    blockers.insert(key, n);
+                }
+            }
+        }
+
+        while !ready.is_empty() {
+            let keys = std::mem::take(&mut ready);
+            // Cells are taken from the map while evaluated, to borrow `values`
+            let mut level: Vec<(Key, Cell)> = keys
+                .iter()
+                .filter_map(|key| self.cells.remove_entry(key))
+                .collect();
+            let results = eval_level(&mut level, &self.values);
+            for ((key, mut cell), result) in level.into_iter().zip(results) {
+                match result {
                     Ok(Some(value)) => {
                         self.values.insert(key, value);
                     }
                     Ok(None) => (),
-                    Err(EvalError::Dependancy) => waiting.push(key),
+                    // The dependency is of an earlier level, so is in the map
+                    Err(EvalError::Dependancy(dep)) => cell.set_ref_error(self.blame(dep)),
+                    Err(EvalError::Type) => cell.set_ref_error(RefError::Type),
+                    Err(EvalError::Name) => cell.set_ref_error(RefError::Name),
+                }
+                self.cells.insert(key, cell);
+            }
+
+            for key in keys {
+                for dependent in self.dependents_of(key) {
+                    if let Some(count) = blockers.get_mut(&dependent) {
+                        *count -= 1;
+                        if *count == 0 {
+                            blockers.remove(&dependent);
+                            ready.push(dependent);
+                        }
+                    }
                 }
             }
+        }
 
-            if waiting.len() >= remaining {
-                for key in waiting.drain(..) {
-                    let cell = self.cells.get_mut(&key).unwrap();
-                    cell.display = "Ref error".to_string();
+        // Remaining cells are in or depend on a cycle
+        let blocked: HashSet<Key> = blockers.into_keys().collect();
+        for component in Components::find(&self.cells, &blocked) {
+            let key = component[0];
+            let cell = &self.cells[&key];
+            if component.len() > 1 || cell.depends_on(key) {
+                for key in component {
+                    self.cells
+                        .get_mut(&key)
+                        .unwrap()
+                        .set_ref_error(RefError::Cycle);
                 }
-                return;
             } else {
-                remaining = waiting.len();
+                // Dependencies within blocked were assigned an error first
+                let dep = cell.deps_within(&blocked).into_iter().min();
+                let error = dep.map(|dep| self.blame(dep)).unwrap_or(RefError::Cycle);
+                self.cells.get_mut(&key).unwrap().set_ref_error(error);
             }
         }
     }
+
+    /// The error of a cell depending on `dep`, which has no value
+    fn blame(&self, dep: Key) -> RefError {
+        match self.cells.get(&dep).and_then(|cell| cell.ref_error) {
+            Some(RefError::Via(other)) => RefError::Via(other),
+            _ => RefError::Via(dep),
+        }
+    }
+}
+
+/// Evaluate a level of cells, which do not depend on each other
+///
+/// With the `parallel` feature, large levels are evaluated on rayon's thread pool.
+fn eval_level(
+    level: &mut [(Key, Cell)],
+    values: &HashMap<Key, CellValue>,
+) -> Vec<Result<Option<CellValue>, EvalError>> {
+    let eval = |(_, cell): &mut (Key, Cell)| cell.try_eval(values);
+    #[cfg(feature = "parallel")]
+    if level.len() >= PARALLEL_LEVEL {
+        use rayon::prelude::*;
+        return level.par_iter_mut().map(eval).collect();
+    }
+    level.iter_mut().map(eval).collect()
+}
+
+/// A named sheet
+///
+/// The UI holds the data and undo history of the active sheet; the sheet's
+/// own fields are then empty.
+#[derive(Debug)]
+struct Sheet {
+    name: String,
+    data: CellData,
+    undo: UndoStack<Vec<(Key, String)>>,
+}
+
+impl Sheet {
+    fn new(name: String, data: CellData) -> Self {
+        let undo = UndoStack::default();
+        Sheet { name, data, undo }
+    }
+}
+
+/// A sheet as saved by [`save_sheets`]
+#[derive(Serialize, Deserialize)]
+struct SavedSheet {
+    name: String,
+    /// Defined names, with ranges as given by [`range_text`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    names: BTreeMap<String, String>,
+    cells: BTreeMap<Key, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    notes: BTreeMap<Key, String>,
+}
+
+/// Save the inputs of used cells of each sheet, given with its name, to `path` as JSON
+fn save_sheets<'a>(
+    path: &Path,
+    sheets: impl Iterator<Item = (&'a str, &'a CellData)>,
+) -> io::Result<()> {
+    let sheets = sheets.map(|(name, data)| SavedSheet {
+        name: name.to_string(),
+        names: data
+            .names
+            .iter()
+            .map(|(name, (a, b))| (name.clone(), range_text(*a, *b)))
+            .collect(),
+        cells: data.inputs(),
+        notes: data.notes(),
+    });
+    let text = serde_json::to_string_pretty(&sheets.collect::<Vec<_>>())?;
+    std::fs::write(path, text)
+}
+
+/// Load sheets saved by [`save_sheets`]
+///
+/// Files holding the cells of a single, unnamed sheet are also accepted.
+fn load_sheets(path: &Path) -> io::Result<Vec<Sheet>> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Saved {
+        Sheets(Vec<SavedSheet>),
+        Cells(BTreeMap<Key, String>),
+    }
+
+    let text = std::fs::read_to_string(path)?;
+    let sheets = match serde_json::from_str(&text)? {
+        Saved::Sheets(sheets) => sheets,
+        Saved::Cells(cells) => vec![SavedSheet {
+            name: "Sheet1".to_string(),
+            names: BTreeMap::new(),
+            cells,
+            notes: BTreeMap::new(),
+        }],
+    };
+    if sheets.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "no sheets"));
+    }
+    let sheets = sheets.into_iter().map(|s| {
+        let mut data = CellData::from_inputs(s.names, s.cells);
+        for (key, note) in s.notes {
+            data.set_note(key, note);
+        }
+        Sheet::new(s.name, data)
+    });
+    Ok(sheets.collect())
+}
+
+/// Style of tables written by [`CellData::write_html`]
+const HTML_STYLE: &str = "<style>
+table { border-collapse: collapse; font-family: sans-serif; }
+th, td { border: 1px solid #aaa; padding: 2px 6px; }
+th { background: #eee; }
+td[title] { text-decoration: underline dotted; }
+</style>
+";
+
+/// Escape `text` for use in HTML content or a quoted attribute
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Parse a cell like "B1" or a range like "A1:B5", ignoring any `$`
+fn parse_range(s: &str) -> Option<(Key, Key)> {
+    let s = s.replace('$', "");
+    let (a, b) = s.split_once(':').unwrap_or((&s, &s));
+    Some((Key::parse(a.trim())?, Key::parse(b.trim())?))
+}
+
+/// Write a cell like "B1" or, if the corners differ, a range like "A1:B5"
+fn range_text(a: Key, b: Key) -> String {
+    match a == b {
+        true => a.to_string(),
+        false => format!("{a}:{b}"),
+    }
+}
+
+/// A tab selecting sheet `index`
+fn sheet_tab(name: &str, index: usize) -> RadioButton<usize> {
+    let active = move |_: &ConfigCx, active: &usize| *active == index;
+    RadioButton::new_msg(name.to_string(), active, SwitchSheet(index))
+}
+
+/// Replace each `query` in `text` (ignoring ASCII case) with `with`, if there are any
+fn replace_ignore_case(text: &str, query: &str, with: &str) -> Option<String> {
+    // ASCII case conversion keeps byte offsets
+    let lower = text.to_ascii_lowercase();
+    let query = query.to_ascii_lowercase();
+    let mut matches = lower.match_indices(&query).peekable();
+    matches.peek()?;
+    let mut result = String::new();
+    let mut last = 0;
+    for (i, _) in matches {
+        result.push_str(&text[last..i]);
+        result.push_str(with);
+        last = i + query.len();
+    }
+    result.push_str(&text[last..]);
+    Some(result)
+}
+
+/// Order of values when sorting: numbers, then text (ignoring case), then empty cells
+///
+/// If `descending`, the order is reversed except that empty cells remain last.
+fn sort_order(a: Option<&CellValue>, b: Option<&CellValue>, descending: bool) -> Ordering {
+    let (a, b) = match (a, b) {
+        (Some(a), Some(b)) => (a, b),
+        (a, b) => return a.is_none().cmp(&b.is_none()),
+    };
+    let order = match (a, b) {
+        (CellValue::Number(x), CellValue::Number(y)) => x.total_cmp(y),
+        (CellValue::Number(_), CellValue::Text(_)) => Ordering::Less,
+        (CellValue::Text(_), CellValue::Number(_)) => Ordering::Greater,
+        (CellValue::Text(x), CellValue::Text(y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+    };
+    if descending {
+        order.reverse()
+    } else {
+        order
+    }
+}
+
+/// Adjust `input` for a copy `cols` columns right and `rows` rows down, as when filling
+///
+/// Relative references in a formula move with it: `=A2 + $A$1` filled down
+/// becomes `=A3 + $A$1`.
+/// Returns `None` if a reference would leave the sheet. Other inputs, including
+/// formulas which fail to parse, are copied unchanged.
+pub fn offset_input(input: &str, cols: i32, rows: i32) -> Option<String> {
+    let (_, body) = NumberFormat::split(input);
+    let format = &input[..input.len() - body.len()];
+    match parser::parse(body) {
+        Ok(Some(formula)) => Some(format!("{format}={}", formula.offset(cols, rows)?)),
+        _ => Some(input.to_string()),
+    }
+}
+
+/// True if `event` is a press of Ctrl (without Shift) and the letter `c`
+fn is_ctrl_letter(cx: &EventState, event: &Event, c: &str) -> bool {
+    let modifiers = cx.modifiers();
+    match event {
+        Event::Key(event, _) if event.state == ElementState::Pressed => {
+            let is_c = match event.logical_key.as_ref() {
+                kas::event::Key::Character(s) => s.eq_ignore_ascii_case(c),
+                _ => false,
+            };
+            is_c && modifiers.control_key() && !modifiers.shift_key()
+        }
+        _ => false,
+    }
+}
+
+/// Evaluate each line of `input` as a cell in column A, returning displayed values
+///
+/// This runs the formula parser and evaluator without a UI (e.g. for fuzzing).
+/// Lines beyond the last row are ignored.
+pub fn evaluate_column(input: &str) -> Vec<String> {
+    evaluate_column_with_names(input, &[])
+}
+
+/// As [`evaluate_column`], with `names` defined first
+///
+/// Each name is given with a cell like "B1" or a range like "A1:A5". Invalid
+/// names are skipped.
+pub fn evaluate_column_with_names(input: &str, names: &[(&str, &str)]) -> Vec<String> {
+    let names = names
+        .iter()
+        .map(|(name, range)| (name.to_string(), range.to_string()));
+    let mut data = CellData::from_inputs(names.collect(), BTreeMap::new());
+    let mut keys = vec![];
+    for (row, line) in (1..=MAX_ROW).zip(input.lines()) {
+        let key = Key(ColKey(0), row);
+        let _ = data.update_cell(key, line.to_string());
+        keys.push(key);
+    }
+    data.update_values(keys.iter().copied());
+    let cells = keys.iter().map_while(|key| data.cells.get(key));
+    cells.map(|cell| cell.display()).collect()
 }
 
 #[derive(Clone, Debug, Default)]
@@ -350,8 +2081,15 @@ struct Item {
     input: String,
     display: String,
     error: bool,
+    /// Shown in the status bar on focus
+    status: String,
 }
 
+/// Appended to the displayed value of cells with a note
+///
+/// `EditBox` cannot draw a marker in the cell's corner, so this stands in.
+const NOTE_MARKER: &str = " ◥";
+
 impl SharedData for CellData {
     type Key = Key;
     type Item = Item;
@@ -363,28 +2101,32 @@ impl SharedData for CellData {
     }
 
     fn borrow(&self, key: &Self::Key) -> Option<Self::Item> {
-        self.cells
-            .get(key)
-            .map(|cell| Item {
-                input: cell.input.clone(),
-                display: cell.display(),
-                error: cell.parse_error,
-            })
-            .or_else(|| Some(Item::default()))
+        let cell = self.cells.get(key);
+        Some(Item {
+            input: cell.map(|cell| cell.input.clone()).unwrap_or_default(),
+            display: cell
+                .map(|cell| match cell.note.is_empty() {
+                    true => cell.display(),
+                    false => cell.display() + NOTE_MARKER,
+                })
+                .unwrap_or_default(),
+            error: cell.is_some_and(|cell| cell.parse_error.is_some()),
+            status: self.status(*key),
+        })
     }
 }
 
 impl MatrixData for CellData {
     type ColKey = ColKey;
-    type RowKey = u8;
+    type RowKey = u32;
     type ColKeyIter<'b> = iter::Take<iter::Skip<ColKeyIter>>;
-    type RowKeyIter<'b> = iter::Take<iter::Skip<ops::RangeInclusive<u8>>>;
+    type RowKeyIter<'b> = iter::Take<iter::Skip<ops::RangeInclusive<u32>>>;
 
     fn is_empty(&self) -> bool {
         false
     }
     fn len(&self) -> (usize, usize) {
-        (ColKey::LEN.cast(), 99)
+        (ColKey::LEN.cast(), MAX_ROW.cast())
     }
 
     fn col_iter_from(&self, start: usize, limit: usize) -> Self::ColKeyIter<'_> {
@@ -405,10 +2147,83 @@ impl MatrixData for CellData {
 #[derive(Debug)]
 struct UpdateInput(Key, String);
 
+/// Cell `.0` gained focus
+#[derive(Debug)]
+struct FocusCell(Key);
+
+#[derive(Debug)]
+struct ImportCsv;
+
+#[derive(Debug)]
+struct ExportCsv;
+
+#[derive(Debug)]
+struct ExportHtml;
+
+#[derive(Debug)]
+struct SetExportInputs(bool);
+
+#[derive(Debug)]
+struct SetTraceDependents(bool);
+
+/// Sort rows by the focused cell's column; descending if true
+#[derive(Debug)]
+struct SortRows(bool);
+
+#[derive(Debug)]
+struct FindNext;
+
+#[derive(Clone, Debug)]
+struct SwitchSheet(usize);
+
+#[derive(Debug)]
+struct AddSheet;
+
+#[derive(Debug)]
+struct RenameSheet;
+
+#[derive(Debug)]
+struct ReplaceAll;
+
+#[derive(Debug)]
+struct DefineName;
+
+#[derive(Debug)]
+struct RemoveName;
+
+#[derive(Debug)]
+struct SetNote;
+
+/// Guard for the note field: Enter sets the note
+#[derive(Debug, Default)]
+struct NoteGuard;
+impl EditGuard for NoteGuard {
+    type Data = ();
+
+    fn activate(_: &mut EditField<Self>, cx: &mut EventCx, _: &()) -> IsUsed {
+        cx.push(SetNote);
+        IsUsed::Used
+    }
+}
+
+/// Guard for the find field: Enter finds the next match
+#[derive(Debug, Default)]
+struct FindGuard;
+impl EditGuard for FindGuard {
+    type Data = ();
+
+    fn activate(_: &mut EditField<Self>, cx: &mut EventCx, _: &()) -> IsUsed {
+        cx.push(FindNext);
+        IsUsed::Used
+    }
+}
+
 #[derive(Clone, Default, Debug)]
 struct CellGuard {
     key: Key,
     is_input: bool,
+    /// The input when editing began
+    input: String,
 }
 impl EditGuard for CellGuard {
     type Data = Item;
@@ -418,6 +2233,10 @@ impl EditGuard for CellGuard {
         if !edit.has_edit_focus() {
             action |= edit.set_str(&item.display);
             edit.guard.is_input = false;
+        } else if edit.guard.is_input && item.input != edit.guard.input {
+            // The input was replaced while editing (by pasting a block or filling)
+            action |= edit.set_str(&item.input);
+            edit.guard.input = item.input.clone();
         }
         cx.action(edit, action);
     }
@@ -429,7 +2248,10 @@ impl EditGuard for CellGuard {
 
     fn focus_gained(edit: &mut EditField<Self>, cx: &mut EventCx, item: &Item) {
         cx.action(edit.id(), edit.set_str(&item.input));
+        cx.push(Status::Message(item.status.clone()));
+        cx.push(FocusCell(edit.guard.key));
         edit.guard.is_input = true;
+        edit.guard.input = item.input.clone();
     }
 
     fn focus_lost(edit: &mut EditField<Self>, cx: &mut EventCx, item: &Item) {
@@ -452,67 +2274,747 @@ impl Driver<Item, CellData> for CellDriver {
     fn make(&mut self, key: &Key) -> Self::Widget {
         EditBox::new(CellGuard {
             key: *key,
-            is_input: false,
+            ..Default::default()
         })
     }
 }
 
-pub fn window() -> Window<()> {
+pub fn ui() -> impl Widget<Data = Shared> {
     let mut data = CellData::new();
-    let cells = &mut data.cells;
-    cells.insert(make_key("A1"), Cell::new("Some values"));
-    cells.insert(make_key("A2"), Cell::new("3"));
-    cells.insert(make_key("A3"), Cell::new("4"));
-    cells.insert(make_key("A4"), Cell::new("5"));
-    cells.insert(make_key("B1"), Cell::new("Sum"));
-    cells.insert(make_key("B2"), Cell::new("= A2 + A3 + A4"));
-    cells.insert(make_key("C1"), Cell::new("Prod"));
-    cells.insert(make_key("C2"), Cell::new("= A2 * A3 * A4"));
-    data.update_values();
-
-    let cells = MatrixView::new(CellDriver).with_num_visible(5, 20);
+    let inputs = [
+        ("A1", "Some values"),
+        ("A2", "3"),
+        ("A3", "4"),
+        ("A4", "5"),
+        ("B1", "Sum"),
+        ("B2", "= A2 + A3 + A4"),
+        ("C1", "Prod"),
+        ("C2", "= A2 * A3 * A4"),
+    ];
+    for (key, input) in inputs {
+        let _ = data.update_cell(make_key(key), input.to_string());
+    }
+    data.update_values(inputs.map(|(key, _)| make_key(key)));
+    sheet_ui(data, false)
+}
+
+/// Number of columns filled by [`stress_window`]
+const STRESS_COLS: u16 = 26;
+
+/// Fill whole rows of columns A to Z, at least `n` cells if the sheet has room
+///
+/// Each cell below the first row adds A1 to the cell above it, so editing
+/// A1 re-evaluates every cell.
+fn stress_data(n: usize) -> CellData {
+    let mut data = CellData::new();
+    let rows = n.div_ceil(STRESS_COLS.into()).min(MAX_ROW as usize) as u32;
+    let mut keys = Vec::with_capacity(rows as usize * usize::from(STRESS_COLS));
+    for row in 1..=rows {
+        for col in (0..STRESS_COLS).map(ColKey) {
+            let input = match row {
+                1 => (col.0 + 1).to_string(),
+                _ => format!("={col}{} + $A$1", row - 1),
+            };
+            let key = Key(col, row);
+            let _ = data.update_cell(key, input);
+            keys.push(key);
+        }
+    }
+    data.update_values(keys);
+    data
+}
+
+/// Construct a Cells window with `thousands` thousand generated cells, for profiling
+///
+/// Timings of generation and of each cell update are printed to stderr.
+pub fn stress_window(thousands: u32) -> Window<Shared> {
+    let n = (thousands as usize).saturating_mul(1000);
+    let start = Instant::now();
+    let data = stress_data(n);
+    let cells = data.cells.len();
+    eprintln!(
+        "cells: generated and evaluated {cells} cells in {:?}",
+        start.elapsed()
+    );
+    let title = format!("{WINDOW_TITLE} (stress: {cells} cells)");
+    demo_window(sheet_ui(data, true), MENU, &title)
+}
+
+/// Construct the UI over `data`; if `timed`, print the time taken by each cell update
+fn sheet_ui(data: CellData, timed: bool) -> impl Widget<Data = Shared> {
+    let cells = MatrixView::new(CellDriver)
+        .with_num_visible(5, 20)
+        .with_selection_mode(SelectionMode::Multiple);
+    let dir = dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_default();
+    let csv_path = dir.join("cells.csv").display().to_string();
+    let sheet_path = dir.join("cells.json");
 
     let ui = impl_anon! {
         #[widget {
-            layout = self.cells;
+            layout = column! [
+                row! [
+                    "CSV file:",
+                    self.csv_path,
+                    self.export_inputs,
+                    Button::label_msg("&Import CSV", ImportCsv),
+                    Button::label_msg("E&xport CSV", ExportCsv),
+                    Button::label_msg("Export HTM&L", ExportHtml),
+                    Button::label_msg("Sort &ascending", SortRows(false)),
+                    Button::label_msg("Sort &descending", SortRows(true)),
+                    self.trace,
+                ],
+                row! [
+                    "Find:",
+                    self.find,
+                    "Replace with:",
+                    self.replacement,
+                    Button::label_msg("Find &next", FindNext),
+                    Button::label_msg("&Replace all", ReplaceAll),
+                ],
+                row! [
+                    "Name:",
+                    self.name,
+                    "refers to:",
+                    self.name_range,
+                    Button::label_msg("Se&t name", DefineName),
+                    Button::label_msg("&Clear name", RemoveName),
+                    self.names,
+                ],
+                row! [
+                    "Note:",
+                    self.note,
+                    Button::label_msg("&Update note", SetNote),
+                ],
+                self.cells,
+                row! [
+                    self.tabs,
+                    "Sheet name:",
+                    self.sheet_name,
+                    Button::label_msg("Add &sheet", AddSheet),
+                    Button::label_msg("Rena&me sheet", RenameSheet),
+                ],
+            ];
         }]
         struct {
             core: widget_core!(),
             data: CellData = data,
             #[widget(&self.data)] cells: ScrollBars<MatrixView<CellData, CellDriver>> =
                 ScrollBars::new(cells),
+            #[widget] csv_path: EditBox = EditBox::text(csv_path),
+            #[widget] find: EditBox<FindGuard> = EditBox::new(FindGuard),
+            #[widget] replacement: EditBox = EditBox::text(""),
+            #[widget] name: EditBox = EditBox::text(""),
+            #[widget] name_range: EditBox = EditBox::text(""),
+            #[widget] note: EditBox<NoteGuard> = EditBox::new(NoteGuard),
+            #[widget(&self.data)] names: Text<CellData, String> =
+                Text::new(|_, data: &CellData| data.names_text()),
+            #[widget(&self.active)] tabs: Row<RadioButton<usize>> =
+                Row::new(vec![sheet_tab("Sheet1", 0)]),
+            #[widget] sheet_name: EditBox = EditBox::text(""),
+            /// All sheets; see [`Sheet`]
+            sheets: Vec<Sheet> = vec![Sheet::new("Sheet1".to_string(), CellData::new())],
+            /// Index of the active sheet, whose data is `data`
+            active: usize = 0,
+            #[widget(&self.inputs)] export_inputs: CheckButton<bool> = CheckButton::new_msg(
+                "Export as f&ormulas",
+                |_, inputs: &bool| *inputs,
+                SetExportInputs,
+            ),
+            /// Export inputs instead of displayed values
+            inputs: bool = false,
+            #[widget(&self.trace_dependents)] trace: CheckButton<bool> = CheckButton::new_msg(
+                "Show de&pendents",
+                |_, trace: &bool| *trace,
+                SetTraceDependents,
+            ),
+            /// Also highlight the cells referencing the focused cell
+            trace_dependents: bool = false,
+            /// Used by File → Save and File → Open
+            sheet_path: PathBuf = sheet_path,
+            /// Where the clipboard text was copied from, if copied from the sheet
+            ///
+            /// Pasting this text adjusts references for the new position.
+            copied: Option<(Key, String)>,
+            /// The selected rectangle, from the cell where it started to the focused cell
+            selection: Option<(Key, Key)>,
+            /// Whether the next focused cell extends the selection (Shift is held)
+            extend: bool,
+            /// Inputs replaced by clearing the selection, sorting or replacing
+            undo: UndoStack<Vec<(Key, String)>>,
+            /// Print the time taken by each cell update (see [`stress_window`])
+            timed: bool = timed,
+        }
+        impl Self {
+            fn save(&mut self, cx: &mut EventCx) {
+                let sheets = self.sheets.iter().enumerate().map(|(index, sheet)| {
+                    let data = if index == self.active { &self.data } else { &sheet.data };
+                    (sheet.name.as_str(), data)
+                });
+                match save_sheets(&self.sheet_path, sheets) {
+                    Ok(()) => {
+                        let message = format!("Saved to {}", self.sheet_path.display());
+                        cx.push(Status::Message(message));
+                    }
+                    Err(error) => cx.push(Toast(format!("Save failed: {error}"))),
+                }
+            }
+
+            fn open(&mut self, cx: &mut EventCx) {
+                match load_sheets(&self.sheet_path) {
+                    Ok(sheets) => {
+                        self.sheets = sheets;
+                        self.active = 0;
+                        self.data = CellData::new();
+                        self.undo = UndoStack::default();
+                        self.swap_active();
+                        self.deselect(cx);
+                        self.rebuild_tabs(cx);
+                        cx.update(self.as_node(&()));
+                        let message = format!("Opened {}", self.sheet_path.display());
+                        cx.push(Status::Message(message));
+                    }
+                    Err(error) => cx.push(Toast(format!("Open failed: {error}"))),
+                }
+            }
+
+            /// Swap the data and undo history of the active sheet with `data` and `undo`
+            fn swap_active(&mut self) {
+                let sheet = &mut self.sheets[self.active];
+                std::mem::swap(&mut self.data, &mut sheet.data);
+                std::mem::swap(&mut self.undo, &mut sheet.undo);
+            }
+
+            fn switch_sheet(&mut self, cx: &mut EventCx, index: usize) {
+                if index != self.active {
+                    self.swap_active();
+                    self.active = index;
+                    self.swap_active();
+                    self.deselect(cx);
+                }
+                cx.update(self.as_node(&()));
+            }
+
+            fn deselect(&mut self, cx: &mut EventCx) {
+                self.selection = None;
+                let action = self.cells.inner_mut().clear_selected();
+                cx.action(&self, action);
+            }
+
+            /// Replace the tabs to match `sheets`
+            fn rebuild_tabs(&mut self, cx: &mut EventCx) {
+                for index in (0..self.tabs.len()).rev() {
+                    self.tabs.remove(cx, index);
+                }
+                let mut cx = cx.config_cx();
+                for (index, sheet) in self.sheets.iter().enumerate() {
+                    self.tabs.push(&mut cx, &self.active, sheet_tab(&sheet.name, index));
+                }
+            }
+
+            fn add_sheet(&mut self, cx: &mut EventCx) {
+                let names = (1..).map(|n| format!("Sheet{n}"));
+                let mut names = names.filter(|name| self.sheets.iter().all(|s| s.name != *name));
+                let name = names.next().unwrap();
+                let index = self.sheets.len();
+                let tab = sheet_tab(&name, index);
+                self.tabs.push(&mut cx.config_cx(), &self.active, tab);
+                self.sheets.push(Sheet::new(name, CellData::new()));
+                self.switch_sheet(cx, index);
+            }
+
+            /// Rename the active sheet to the text of the sheet name field
+            fn rename_sheet(&mut self, cx: &mut EventCx) {
+                let name = self.sheet_name.get_string().trim().to_string();
+                if name.is_empty() {
+                    cx.push(Toast("Enter a name for the sheet".to_string()));
+                } else if self.sheets.iter().any(|sheet| sheet.name == name) {
+                    cx.push(Toast(format!("There is already a sheet named {name}")));
+                } else {
+                    self.sheets[self.active].name = name;
+                    self.rebuild_tabs(cx);
+                }
+            }
+
+            /// Select the rectangle from `anchor` to `key`
+            ///
+            /// Rectangles larger than [`MAX_HIGHLIGHTED`] cells are not
+            /// highlighted, to bound the cost. A single cell is not highlighted
+            /// itself; instead its precedents (and optionally dependents) are.
+            fn select(&mut self, cx: &mut EventCx, anchor: Key, key: Key) {
+                self.selection = Some((anchor, key));
+                let view = self.cells.inner_mut();
+                let mut action = view.clear_selected();
+                if anchor == key {
+                    for key in self.data.traced(key, self.trace_dependents) {
+                        action |= view.select(key);
+                    }
+                } else if area(anchor, key) <= MAX_HIGHLIGHTED {
+                    for key in range_keys(anchor, key) {
+                        action |= view.select(key);
+                    }
+                }
+                cx.action(&self, action);
+            }
+
+            /// Clear the selected cells, if more than one
+            fn clear_selection(&mut self, cx: &mut EventCx) -> IsUsed {
+                let Some((a, b)) = self.selection.filter(|(a, b)| a != b) else {
+                    return IsUsed::Unused;
+                };
+                let prev = self.data.clear(a, b);
+                let (a, b) = corners(a, b);
+                cx.push(Status::Message(format!("Cleared {a}:{b}")));
+                self.undo.push(prev);
+                cx.update(self.as_node(&()));
+                IsUsed::Used
+            }
+
+            /// Sort the rows of the selection (or, if none, of the sheet)
+            ///
+            /// Rows are ordered by the values in the focused cell's column.
+            fn sort_rows(&mut self, cx: &mut EventCx, descending: bool) {
+                let Some((anchor, key)) = self.selection else {
+                    cx.push(Toast("Focus a cell in the column to sort by".to_string()));
+                    return;
+                };
+                let selection = Some((anchor, key)).filter(|(a, b)| a != b);
+                let Some((a, b)) = selection.or_else(|| self.data.used_range()) else {
+                    return;
+                };
+                let prev = self.data.sort_rows(a, b, key.0, descending);
+                let (a, b) = corners(a, b);
+                cx.push(Status::Message(format!("Sorted {a}:{b} by column {}", key.0)));
+                self.undo.push(prev);
+                cx.update(self.as_node(&()));
+            }
+
+            /// Restore the cells changed by the last clear, sort or replace
+            fn undo(&mut self, cx: &mut EventCx) -> IsUsed {
+                let Some(prev) = self.undo.undo() else {
+                    return IsUsed::Unused;
+                };
+                let n = prev.len();
+                self.data.replace(prev);
+                cx.update(self.as_node(&()));
+                cx.push(Status::Message(format!("Restored {n} cells")));
+                IsUsed::Used
+            }
+
+            fn focused_key(&self, cx: &EventCx) -> Option<Key> {
+                let id = cx.nav_focus()?;
+                Key::reconstruct_key(self.cells.inner().id_ref(), id)
+            }
+
+            /// Move focus from the focused cell by the given offsets, if any cell is focused
+            ///
+            /// The cell losing focus commits its input. If Shift is held, the
+            /// selection is extended to the new cell.
+            fn move_focus(&mut self, cx: &mut EventCx, cols: i16, rows: i32) -> IsUsed {
+                let Some(Key(col, row)) = self.focused_key(cx) else {
+                    return IsUsed::Unused;
+                };
+                self.extend = cx.modifiers().shift_key();
+                let col = col.0.saturating_add_signed(cols).min(ColKey::LEN - 1);
+                let row = row.saturating_add_signed(rows).clamp(1, MAX_ROW);
+                self.focus_cell(cx, Key(ColKey(col), row));
+                IsUsed::Used
+            }
+
+            fn focus_cell(&mut self, cx: &mut EventCx, key: Key) {
+                let id = key.make_id(self.cells.inner().id_ref());
+                cx.next_nav_focus(Some(id), false, FocusSource::Synthetic);
+            }
+
+            /// Focus the next cell matching the find field, after the focused cell
+            fn find_next(&mut self, cx: &mut EventCx) {
+                let query = self.find.get_string();
+                if query.is_empty() {
+                    return;
+                }
+                let from = self.selection.map(|(_, key)| key);
+                match self.data.find(&query, from) {
+                    Some(key) => {
+                        self.extend = false;
+                        self.focus_cell(cx, key);
+                    }
+                    None => cx.push(Toast(format!("No cell contains \"{query}\""))),
+                }
+            }
+
+            /// Replace the find field's text with the replacement in all inputs
+            fn replace_all(&mut self, cx: &mut EventCx) {
+                let query = self.find.get_string();
+                if query.is_empty() {
+                    return;
+                }
+                let prev = self.data.replace_all(&query, &self.replacement.get_string());
+                cx.push(Status::Message(format!("Replaced in {} cells", prev.len())));
+                self.undo.push(prev);
+                cx.update(self.as_node(&()));
+            }
+
+            /// Define the name in the name field as the cell or range in the range field
+            fn define_name(&mut self, cx: &mut EventCx) {
+                let name = self.name.get_string().trim().to_string();
+                let range = self.name_range.get_string();
+                if !parser::is_name(&name) {
+                    cx.push(Toast(format!(
+                        "Bad name \"{name}\": use letters, digits and _, unlike a cell"
+                    )));
+                } else if let Some((a, b)) = parse_range(&range) {
+                    let (a, b) = corners(a, b);
+                    let message = format!("Defined {name} = {}", range_text(a, b));
+                    self.data.set_name(name, Some((a, b)));
+                    cx.update(self.as_node(&()));
+                    cx.push(Status::Message(message));
+                } else {
+                    cx.push(Toast(format!("No cell or range \"{}\"", range.trim())));
+                }
+            }
+
+            /// Remove the name in the name field; cells using it show `#NAME?`
+            fn remove_name(&mut self, cx: &mut EventCx) {
+                let name = self.name.get_string().trim().to_string();
+                if !self.data.names.contains_key(&name) {
+                    cx.push(Toast(format!("No name {name} is defined")));
+                    return;
+                }
+                let message = format!("Removed {name}");
+                self.data.set_name(name, None);
+                cx.update(self.as_node(&()));
+                cx.push(Status::Message(message));
+            }
+
+            /// Attach the note field's text to the last focused cell
+            fn set_note(&mut self, cx: &mut EventCx) {
+                let Some((_, key)) = self.selection else {
+                    cx.push(Toast("Focus a cell to attach the note to".to_string()));
+                    return;
+                };
+                let note = self.note.get_string().trim().to_string();
+                let message = match note.is_empty() {
+                    true => format!("Removed the note of {key}"),
+                    false => format!("Attached the note to {key}"),
+                };
+                self.data.set_note(key, note);
+                cx.update(self.as_node(&()));
+                cx.push(Status::Message(message));
+            }
+
+            /// Paste a block of cells at the focused cell
+            ///
+            /// Other text is pasted into the cell's edit field, unless it was
+            /// copied from a cell of this sheet.
+            fn paste(&mut self, cx: &mut EventCx) -> IsUsed {
+                let Some(text) = clipboard::get_text(cx) else {
+                    return IsUsed::Unused;
+                };
+                let Some(key) = self.focused_key(cx) else {
+                    return IsUsed::Unused;
+                };
+                let copied = self.copied.as_ref().filter(|(_, copied)| *copied == text);
+                let origin = copied.map(|(origin, _)| *origin);
+                if origin.is_none() && !clipboard::is_tsv(&text) {
+                    return IsUsed::Unused;
+                }
+                let mut rows = clipboard::parse_tsv(&text);
+                if let Some(origin) = origin {
+                    let (cols, rows_down) = key.offset_from(origin);
+                    for field in rows.iter_mut().flatten() {
+                        let Some(input) = offset_input(field, cols, rows_down) else {
+                            cx.push(Toast(format!(
+                                "Cannot paste at {key}: a reference would leave the sheet"
+                            )));
+                            return IsUsed::Used;
+                        };
+                        *field = input;
+                    }
+                }
+                let errors = self.data.paste(key, rows);
+                cx.update(self.as_node(&()));
+                if errors > 0 {
+                    cx.push(Toast(format!("Pasted with {errors} bad formulas")));
+                } else {
+                    cx.push(Status::Message(format!("Pasted at {key}")));
+                }
+                IsUsed::Used
+            }
+
+            /// Fill the focused cell from the cell offset by `cols` and `rows` from it
+            fn fill_from(&mut self, cx: &mut EventCx, cols: i32, rows: i32) -> IsUsed {
+                let Some(key) = self.focused_key(cx) else {
+                    return IsUsed::Unused;
+                };
+                // In the first row or column there is nothing to fill from
+                let Some(source) = key.offset(cols, rows) else {
+                    return IsUsed::Used;
+                };
+                let input = self.data.cells.get(&source).map(|cell| cell.input.as_str());
+                match offset_input(input.unwrap_or_default(), -cols, -rows) {
+                    Some(input) => {
+                        let _ = self.data.set_input(key, input);
+                        cx.update(self.as_node(&()));
+                        cx.push(Status::Message(self.data.status(key)));
+                    }
+                    None => cx.push(Toast(format!(
+                        "Cannot fill {key} from {source}: a reference would leave the sheet"
+                    ))),
+                }
+                IsUsed::Used
+            }
         }
         impl Events for Self {
             type Data = ();
 
-            fn steal_event(&mut self, cx: &mut EventCx, _: &(), _: &Id, event: &Event) -> IsUsed {
+            fn configure(&mut self, cx: &mut ConfigCx) {
+                cx.send(self.id(), MenuTarget(self.id()));
+            }
+
+            fn steal_event(&mut self, cx: &mut EventCx, _: &(), id: &Id, event: &Event) -> IsUsed {
                 match event {
+                    Event::Command(Command::Save, _) => {
+                        self.save(cx);
+                        IsUsed::Used
+                    }
+                    Event::Command(Command::Open, _) => {
+                        self.open(cx);
+                        IsUsed::Used
+                    }
                     Event::Command(Command::Enter, _) => {
-                        if let Some(Key(col, row)) = cx.nav_focus().and_then(|id| {
-                            Key::reconstruct_key(self.cells.inner().id_ref(), id)
-                        })
-                        {
-                            let row = if cx.modifiers().shift_key() {
-                                (row - 1).max(1)
-                            } else {
-                                (row + 1).min(MAX_ROW)
-                            };
-                            let id = Key(col, row).make_id(self.cells.inner().id_ref());
-                            cx.next_nav_focus(Some(id), false, FocusSource::Synthetic);
-                        }
+                        let rows = if cx.modifiers().shift_key() { -1 } else { 1 };
+                        self.move_focus(cx, 0, rows)
+                    }
+                    Event::Command(Command::Tab, _) => {
+                        let cols = if cx.modifiers().shift_key() { -1 } else { 1 };
+                        self.move_focus(cx, cols, 0)
+                    }
+                    // Shift+click extends the selection
+                    Event::PressStart { .. } if self.cells.id_ref().is_ancestor_of(id) => {
+                        self.extend = cx.modifiers().shift_key();
+                        IsUsed::Unused
+                    }
+                    Event::Command(Command::Delete, _) => self.clear_selection(cx),
+                    Event::Command(Command::Up, _) => self.move_focus(cx, 0, -1),
+                    Event::Command(Command::Down, _) => self.move_focus(cx, 0, 1),
+                    _ if is_ctrl_letter(cx, event, "d") => self.fill_from(cx, 0, -1),
+                    _ if is_ctrl_letter(cx, event, "r") => self.fill_from(cx, -1, 0),
+                    Event::Command(Command::Copy, _) if cx.modifiers().shift_key() => {
+                        let rows = self.data.used_rows(true);
+                        let n = rows.len();
+                        let text = clipboard::to_tsv(rows);
+                        self.copied = Some((Key(ColKey(0), 1), text.clone()));
+                        clipboard::set_text(cx, text);
+                        cx.push(Status::Message(format!("Copied {n} rows")));
                         IsUsed::Used
-                    },
+                    }
+                    Event::Command(Command::Paste, _) => self.paste(cx),
                     _ => IsUsed::Unused
                 }
             }
 
+            fn handle_event(&mut self, cx: &mut EventCx, _: &(), event: Event) -> IsUsed {
+                match event {
+                    Event::Command(Command::Find, _) => {
+                        let id = self.find.id();
+                        cx.next_nav_focus(Some(id), false, FocusSource::Key);
+                        IsUsed::Used
+                    }
+                    // The focused cell copies selected text; otherwise copy the
+                    // inputs of the selected cells
+                    Event::Command(Command::Copy, _) => {
+                        let Some(key) = self.focused_key(cx) else {
+                            return IsUsed::Unused;
+                        };
+                        if let Some((a, b)) = self.selection.filter(|(a, b)| a != b) {
+                            let text = clipboard::to_tsv(self.data.range_inputs(a, b));
+                            let (a, b) = corners(a, b);
+                            // Pasted references are adjusted relative to the top-left
+                            self.copied = Some((a, text.clone()));
+                            clipboard::set_text(cx, text);
+                            cx.push(Status::Message(format!("Copied {a}:{b}")));
+                            return IsUsed::Used;
+                        }
+                        let cell = self.data.cells.get(&key);
+                        let input = cell.map(|cell| cell.input.clone()).unwrap_or_default();
+                        self.copied = Some((key, input.clone()));
+                        clipboard::set_text(cx, input);
+                        cx.push(Status::Message(format!("Copied {key}")));
+                        IsUsed::Used
+                    }
+                    Event::Command(Command::Undo, _) => self.undo(cx),
+                    _ if snapshot::is_dump_event(cx, &event) => {
+                        snapshot::dump(cx, "cells", &self.data);
+                        IsUsed::Used
+                    }
+                    _ => IsUsed::Unused,
+                }
+            }
+
             fn handle_messages(&mut self, cx: &mut EventCx, _: &()) {
-                if let Some(UpdateInput(key, input)) = cx.try_pop() {
-                    self.data.cells.entry(key).or_default().update(input);
-                    self.data.update_values();
+                if let Some(FocusCell(key)) = cx.try_pop() {
+                    let anchor = match self.selection {
+                        Some((anchor, _)) if self.extend => anchor,
+                        _ => key,
+                    };
+                    self.extend = false;
+                    self.select(cx, anchor, key);
+                    // Show the cell's note for editing
+                    let note = self.data.cells.get(&key).map(|cell| cell.note.clone());
+                    let action = self.note.set_string(note.unwrap_or_default());
+                    cx.action(self.note.id(), action);
+                } else if let Some(EditAction::Undo) = cx.try_pop() {
+                    self.undo(cx);
+                } else if let Some(UpdateInput(key, input)) = cx.try_pop() {
+                    let start = Instant::now();
+                    let result = self.data.set_input(key, input);
+                    if self.timed {
+                        eprintln!("cells: updated {key} in {:?}", start.elapsed());
+                    }
+                    cx.push(Status::Message(self.data.status(key)));
+                    if let Err(error) = result {
+                        cx.push(Toast(format!("Parse error: {error}")));
+                    }
+                } else if let Some(action) = cx.try_pop::<FileAction>() {
+                    match action {
+                        FileAction::Save => self.save(cx),
+                        FileAction::Open => self.open(cx),
+                    }
+                } else if let Some(SetExportInputs(inputs)) = cx.try_pop() {
+                    self.inputs = inputs;
+                    cx.update(self.export_inputs.as_node(&self.inputs));
+                } else if let Some(SetTraceDependents(trace)) = cx.try_pop() {
+                    self.trace_dependents = trace;
+                    cx.update(self.trace.as_node(&self.trace_dependents));
+                    if let Some((anchor, key)) = self.selection {
+                        self.select(cx, anchor, key);
+                    }
+                } else if let Some(ExportCsv) = cx.try_pop() {
+                    let path = PathBuf::from(self.csv_path.get_string());
+                    match self.data.write_csv(&path, self.inputs) {
+                        Ok(n) => {
+                            let message = format!("Exported {n} rows to {}", path.display());
+                            cx.push(Status::Message(message));
+                        }
+                        Err(error) => cx.push(Toast(format!("Export failed: {error}"))),
+                    }
+                } else if let Some(ExportHtml) = cx.try_pop() {
+                    // Written beside the CSV file
+                    let path = PathBuf::from(self.csv_path.get_string()).with_extension("html");
+                    let title = &self.sheets[self.active].name;
+                    match self.data.write_html(&path, title, self.inputs) {
+                        Ok(n) => {
+                            let message = format!("Exported {n} rows to {}", path.display());
+                            cx.push(Status::Message(message));
+                        }
+                        Err(error) => cx.push(Toast(format!("Export failed: {error}"))),
+                    }
+                } else if let Some(SwitchSheet(index)) = cx.try_pop() {
+                    self.switch_sheet(cx, index);
+                } else if let Some(AddSheet) = cx.try_pop() {
+                    self.add_sheet(cx);
+                } else if let Some(RenameSheet) = cx.try_pop() {
+                    self.rename_sheet(cx);
+                } else if let Some(FindNext) = cx.try_pop() {
+                    self.find_next(cx);
+                } else if let Some(ReplaceAll) = cx.try_pop() {
+                    self.replace_all(cx);
+                } else if let Some(SetNote) = cx.try_pop() {
+                    self.set_note(cx);
+                } else if let Some(DefineName) = cx.try_pop() {
+                    self.define_name(cx);
+                } else if let Some(RemoveName) = cx.try_pop() {
+                    self.remove_name(cx);
+                } else if let Some(SortRows(descending)) = cx.try_pop() {
+                    self.sort_rows(cx, descending);
+                } else if let Some(ImportCsv) = cx.try_pop() {
+                    let path = PathBuf::from(self.csv_path.get_string());
+                    match self.data.read_csv(&path) {
+                        Ok((n, errors)) => {
+                            cx.update(self.as_node(&()));
+                            cx.push(Status::Message(format!("Imported {n} rows")));
+                            if errors > 0 {
+                                cx.push(Toast(format!("Imported with {errors} bad formulas")));
+                            }
+                        }
+                        Err(error) => cx.push(Toast(format!("Import failed: {error}"))),
+                    }
                 }
             }
         }
     };
-    Window::new(ui, "Cells")
+    let hint = "Enter, Up, Down: move row; Tab, Shift+Tab: move column; Ctrl+Shift+C: copy sheet";
+    StatusBar::new(ui, hint).map_any()
+}
+
+/// Menu actions
+pub const MENU: MenuSpec = MenuSpec {
+    open_save: true,
+    undo: Some(|cx| cx.push(EditAction::Undo)),
+    shortcuts: &[
+        Shortcut::new("Enter, Down", "Confirm input and move to the next row"),
+        Shortcut::new(
+            "Shift+Enter, Up",
+            "Confirm input and move to the previous row",
+        ),
+        Shortcut::new("Tab", "Confirm input and move to the next column"),
+        Shortcut::new("Shift+Tab", "Confirm input and move to the previous column"),
+        Shortcut::new(
+            "Shift+click, Shift+Up, Shift+Down",
+            "Select a rectangle of cells",
+        ),
+        Shortcut::new(
+            "Delete",
+            "Clear the selected cells (Edit → Undo restores them)",
+        ),
+        Shortcut::new(
+            "Alt+A, Alt+D",
+            "Sort rows of the selection (or sheet) by the focused column",
+        ),
+        Shortcut::new(
+            "Alt+P",
+            "Also highlight the cells referencing the focused cell",
+        ),
+        Shortcut::new("Ctrl+F", "Find (then Enter or Alt+N finds the next match)"),
+        Shortcut::new(
+            "Alt+L",
+            "Export an HTML table beside the CSV file (formulas as tooltips)",
+        ),
+        Shortcut::new("Alt+R", "Replace all matches in cell inputs"),
+        Shortcut::new(
+            "Alt+T, Alt+C",
+            "Define (as a cell or range) or remove the entered name",
+        ),
+        Shortcut::new("Ctrl+D", "Fill down: copy the formula from the cell above"),
+        Shortcut::new(
+            "Ctrl+R",
+            "Fill right: copy the formula from the cell to the left",
+        ),
+        Shortcut::new(
+            "Alt+U",
+            "Attach the note to the last focused cell (empty removes it)",
+        ),
+        Shortcut::new(
+            "Ctrl+C",
+            "Copy the inputs of the selected cells (or selected text)",
+        ),
+        Shortcut::new("Ctrl+Shift+C", "Copy the inputs of all used cells"),
+        Shortcut::new(
+            "Ctrl+V",
+            "Paste (cells at the focused cell, with references adjusted)",
+        ),
+        Shortcut::new("Ctrl+S, Ctrl+O", "Save or open all sheets (cells.json)"),
+        Shortcut::new("Alt+S, Alt+M", "Add a sheet, or rename the current sheet"),
+        Shortcut::new("Ctrl+Shift+D", "Save a debug snapshot"),
+    ],
+    ..MenuSpec::NONE
+};
+
+/// Title of the demo's window
+pub const WINDOW_TITLE: &str = "Cells";
+
+/// Construct the demo in its own window
+pub fn window() -> Window<Shared> {
+    demo_window(ui(), MENU, WINDOW_TITLE)
 }
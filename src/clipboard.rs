@@ -0,0 +1,66 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Clipboard access
+//!
+//! Besides plain text, this supports tab-separated values (TSV): rows of
+//! fields, as exchanged by spreadsheets.
+
+use kas::event::EventState;
+
+/// Get the clipboard contents, if any
+pub fn get_text(cx: &mut EventState) -> Option<String> {
+    cx.get_clipboard().filter(|text| !text.is_empty())
+}
+
+/// Set the clipboard contents
+pub fn set_text(cx: &mut EventState, text: impl Into<String>) {
+    cx.set_clipboard(text.into());
+}
+
+/// Set the clipboard contents to `rows`, as TSV
+pub fn set_tsv<R, F>(cx: &mut EventState, rows: impl IntoIterator<Item = R>)
+where
+    R: IntoIterator<Item = F>,
+    F: AsRef<str>,
+{
+    set_text(cx, to_tsv(rows));
+}
+
+/// Format `rows` as TSV
+///
+/// Tabs and line breaks within fields are replaced by spaces.
+pub fn to_tsv<R, F>(rows: impl IntoIterator<Item = R>) -> String
+where
+    R: IntoIterator<Item = F>,
+    F: AsRef<str>,
+{
+    let mut text = String::new();
+    for row in rows {
+        for (i, field) in row.into_iter().enumerate() {
+            if i > 0 {
+                text.push('\t');
+            }
+            let field = field.as_ref();
+            text.extend(field.chars().map(|c| if c.is_control() { ' ' } else { c }));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Parse TSV `text` into rows of fields
+///
+/// Both `\n` and `\r\n` line endings are accepted.
+pub fn parse_tsv(text: &str) -> Vec<Vec<String>> {
+    text.lines()
+        .map(|line| line.split('\t').map(str::to_string).collect())
+        .collect()
+}
+
+/// True if `text` holds more than one TSV field
+pub fn is_tsv(text: &str) -> bool {
+    text.trim_end_matches(['\r', '\n']).contains(['\t', '\n'])
+}
@@ -0,0 +1,152 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Persistent settings
+
+use kas::messages::MessageStack;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::theme::ColorMode;
+
+/// Change a setting
+///
+/// This is handled by the application data; the config is saved after each
+/// change.
+#[derive(Clone, Debug)]
+pub enum ConfigMsg {
+    Theme(&'static str),
+    ColorMode(ColorMode),
+    Scale(u32),
+    RestoreDemos(bool),
+    OpenDemos(Vec<String>),
+    /// Replace all settings with those re-read from the file (not saved)
+    Reload(Box<Config>),
+}
+
+/// Settings, saved as TOML in the platform's config directory
+///
+/// This is loaded at start-up and available to all windows through
+/// [`crate::data::Shared`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Theme name
+    pub theme: String,
+    pub color_mode: ColorMode,
+    /// UI scale, in percent
+    pub scale: u32,
+    /// Reopen [`Self::open_demos`] at start-up
+    pub restore_demos: bool,
+    /// Names of demos open in the launcher when last used
+    pub open_demos: Vec<String>,
+    /// Seconds to show each demo in kiosk mode (`--kiosk`)
+    pub kiosk_interval: f64,
+    /// Options for each demo, by demo name
+    ///
+    /// For example:
+    /// ```toml
+    /// [demos.timer]
+    /// duration = 15.0
+    /// ```
+    pub demos: BTreeMap<String, toml::Table>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            theme: "flat".to_string(),
+            color_mode: ColorMode::default(),
+            scale: 100,
+            restore_demos: false,
+            open_demos: vec![],
+            kiosk_interval: 30.0,
+            demos: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Get option `key` for demo `name`, if present and valid
+    pub fn demo_option<T: DeserializeOwned>(&self, name: &str, key: &str) -> Option<T> {
+        let value = self.demos.get(name)?.get(key)?;
+        value.clone().try_into().ok()
+    }
+
+    /// Handle [`ConfigMsg`], saving on change
+    pub fn handle_messages(&mut self, messages: &mut MessageStack) {
+        if let Some(msg) = messages.try_pop() {
+            match msg {
+                ConfigMsg::Theme(name) => self.theme = name.to_string(),
+                ConfigMsg::ColorMode(mode) => self.color_mode = mode,
+                ConfigMsg::Scale(scale) => self.scale = scale,
+                ConfigMsg::RestoreDemos(restore) => self.restore_demos = restore,
+                ConfigMsg::OpenDemos(names) => self.open_demos = names,
+                ConfigMsg::Reload(config) => {
+                    *self = *config;
+                    return;
+                }
+            }
+            self.save();
+        }
+    }
+
+    /// Path of the config file, if known
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("kas-7guis").join("config.toml"))
+    }
+
+    /// Load from the config file, falling back to defaults on error
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Config::default();
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).unwrap_or_else(|err| {
+                log::warn!("failed to parse {}: {err}", path.display());
+                Config::default()
+            }),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Config::default(),
+            Err(err) => {
+                log::warn!("failed to read {}: {err}", path.display());
+                Config::default()
+            }
+        }
+    }
+
+    /// Re-read the config file, for example after an external edit
+    ///
+    /// Unlike [`Self::load`], this returns `None` on error (after logging),
+    /// since the file may be only partly written.
+    pub fn reload() -> Option<Self> {
+        let path = Self::path()?;
+        let result = std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| toml::from_str(&text).map_err(|err| err.to_string()));
+        result
+            .map_err(|err| log::warn!("failed to reload {}: {err}", path.display()))
+            .ok()
+    }
+
+    /// Save to the config file, logging any error
+    pub fn save(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+        let result = toml::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+                }
+                std::fs::write(&path, text).map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            log::warn!("failed to save {}: {err}", path.display());
+        }
+    }
+}
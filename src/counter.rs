@@ -5,20 +5,331 @@
 
 //! Counter
 
+use kas::draw::color::Rgba;
+use kas::event::{Command, ElementState, Key};
+use kas::geom::{Quad, Vec2};
+use kas::messages::MessageStack;
 use kas::prelude::*;
-use kas::widgets::{Adapt, Button, EditBox};
+use kas::widgets::{Adapt, Button, Column, EditBox};
+use std::time::Duration;
+
+use crate::animation::{Tween, FRAME};
+use crate::data::Shared;
+use crate::demos::demo_window;
+use crate::menu::MenuSpec;
+use crate::shortcuts::Shortcut;
+use crate::undo::UndoStack;
+use crate::widgets::{NumberActivated, NumberEdited, NumberField, NumberGuard};
+
+/// Change the shared count by some amount
+#[derive(Clone, Debug)]
+struct Change(i32);
+
+/// Set the shared count
+#[derive(Clone, Debug)]
+struct Set(i32);
+
+#[derive(Clone, Debug)]
+struct Undo;
 
 #[derive(Clone, Debug)]
 struct Incr;
 
-pub fn window() -> Window<()> {
+#[derive(Clone, Debug)]
+struct AddCounter;
+
+#[derive(Clone, Debug)]
+struct RemoveCounter(u32);
+
+#[derive(Clone, Debug)]
+struct OpenMirror;
+
+/// Time taken for a new sparkline bar to grow
+const GROW_DURATION: Duration = Duration::from_millis(250);
+
+/// Timer used to animate the sparkline
+const FRAME_TIMER: u64 = 0;
+
+/// Number of samples kept for the sparkline
+const HISTORY_LEN: usize = 40;
+
+/// The main count, shared by all Counter windows
+#[derive(Debug)]
+pub struct Counter {
+    count: i32,
+    /// Recent values of `count`, oldest first
+    history: Vec<i32>,
+    undo: UndoStack<i32>,
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Counter {
+            count: 0,
+            history: vec![0],
+            undo: UndoStack::default(),
+        }
+    }
+}
+
+impl Counter {
+    fn set(&mut self, count: i32) {
+        self.count = count;
+        if self.history.len() == HISTORY_LEN {
+            self.history.remove(0);
+        }
+        self.history.push(count);
+    }
+
+    /// The current count
+    pub fn count(&self) -> i32 {
+        self.count
+    }
+
+    /// Recent values of the count, oldest first
+    pub fn history(&self) -> &[i32] {
+        &self.history
+    }
+
+    /// True if there is a change to undo
+    pub fn can_undo(&self) -> bool {
+        self.undo.can_undo()
+    }
+
+    /// Change the count by `delta`
+    ///
+    /// The count may be set to `i32::MAX` or `MIN`; going further is ignored.
+    pub fn change(&mut self, delta: i32) {
+        if let Some(count) = self.count.checked_add(delta) {
+            self.undo.push(self.count);
+            self.set(count);
+        }
+    }
+
+    /// Set the count, if different
+    pub fn set_count(&mut self, count: i32) {
+        if count != self.count {
+            self.undo.push(self.count);
+            self.set(count);
+        }
+    }
+
+    /// Revert the last change, if any
+    pub fn undo(&mut self) {
+        if let Some(count) = self.undo.undo() {
+            self.set(count);
+        }
+    }
+
+    /// Handle [`Change`], [`Set`] and [`Undo`] messages from any Counter window
+    pub fn handle_messages(&mut self, messages: &mut MessageStack) {
+        let _span = tracing::trace_span!("Counter::handle_messages").entered();
+        if let Some(Change(delta)) = messages.try_pop() {
+            self.change(delta);
+        } else if let Some(Set(count)) = messages.try_pop() {
+            self.set_count(count);
+        } else if let Some(Undo) = messages.try_pop() {
+            self.undo();
+        }
+    }
+}
+
+/// Height fraction (0 to 1) of each bar of a sparkline over `values`
+///
+/// The range is computed in `f64` since it may exceed `i32::MAX`.
+pub fn sparkline_fractions(values: &[i32]) -> Vec<f32> {
+    let min = values.iter().copied().min().unwrap_or(0) as f64;
+    let max = values.iter().copied().max().unwrap_or(0) as f64;
+    let range = (max - min).max(1.0);
+    let frac = |value: i32| ((value as f64 - min) / range) as f32;
+    values.iter().map(|value| frac(*value)).collect()
+}
+
+impl_scope! {
+    /// A tiny bar chart of the count's recent history
+    ///
+    /// The newest bar grows into place.
+    #[impl_default]
+    #[widget]
+    struct Sparkline {
+        core: widget_core!(),
+        values: Vec<i32>,
+        /// Height fraction of the newest bar
+        grow: Tween = Tween::new(1.0, GROW_DURATION),
+    }
+
+    impl Layout for Self {
+        fn size_rules(&mut self, sizer: SizeCx, axis: AxisInfo) -> SizeRules {
+            let em = if axis.is_horizontal() { 6.0 } else { 1.5 };
+            SizeRules::fixed((sizer.dpem() * em).cast_nearest(), (0, 0))
+        }
+
+        fn draw(&mut self, mut draw: DrawCx) {
+            let rect = self.rect();
+            let width = rect.size.0 as f32 / HISTORY_LEN as f32;
+            let col = Rgba::grey(0.5);
+
+            let x0 = rect.pos.0 as f32 + rect.size.0 as f32 - width * self.values.len() as f32;
+            let y1 = (rect.pos.1 + rect.size.1) as f32;
+            let last = self.values.len().wrapping_sub(1);
+            let fractions = sparkline_fractions(&self.values);
+            for (i, mut frac) in fractions.into_iter().enumerate() {
+                if i == last {
+                    frac *= self.grow.value();
+                }
+                let height = 1.0 + frac * (rect.size.1 as f32 - 1.0);
+                let x = x0 + width * i as f32;
+                let quad = Quad::from_coords(
+                    Vec2(x, y1 - height),
+                    Vec2(x + (width - 1.0).max(1.0), y1),
+                );
+                draw.draw_device().rect(quad, col);
+            }
+        }
+    }
+
+    impl Events for Self {
+        type Data = Vec<i32>;
+
+        fn update(&mut self, cx: &mut ConfigCx, values: &Vec<i32>) {
+            if *values != self.values {
+                // Animate only additions, not the initial values
+                if !self.values.is_empty() {
+                    self.grow.jump(0.0);
+                    self.grow.set(1.0);
+                    cx.request_timer(self.id(), FRAME_TIMER, FRAME);
+                }
+                self.values = values.clone();
+                cx.redraw(self);
+            }
+        }
+
+        fn handle_event(&mut self, cx: &mut EventCx, _: &Vec<i32>, event: Event) -> IsUsed {
+            match event {
+                Event::Timer(FRAME_TIMER) => {
+                    cx.redraw(self.id());
+                    if self.grow.is_running() {
+                        cx.request_timer(self.id(), FRAME_TIMER, FRAME);
+                    }
+                    IsUsed::Used
+                }
+                _ => IsUsed::Unused,
+            }
+        }
+    }
+}
+
+/// An additional, independent counter with a remove button
+fn extra_counter(id: u32) -> Box<dyn Widget<Data = ()>> {
     let ui = kas::row![
         align!(
             right,
             EditBox::string(|count| format!("{count}")).with_width_em(3.0, 3.0)
         ),
         Button::label_msg("Count", Incr).map_any(),
+        Button::label_msg("Remove", RemoveCounter(id)).map_any(),
     ];
-    let ui = Adapt::new(ui, 0).on_message(|_, count, Incr| *count += 1);
-    Window::new(ui, "Counter")
+    Box::new(Adapt::new(ui, 0).on_message(|_, count, Incr| *count += 1))
+}
+
+pub fn ui() -> impl Widget<Data = Shared> {
+    let ui = impl_anon! {
+        #[widget {
+            layout = column! [
+                row! [
+                    align!(
+                        right,
+                        NumberField::new(
+                            NumberGuard::new((), |counter: &Counter| counter.count as f64)
+                                .with_limits(|_| (i32::MIN as f64, i32::MAX as f64))
+                                .with_step(1.0)
+                        )
+                        .with_width_em(3.0, 3.0)
+                    ),
+                    Button::label_msg("Count", Change(1)).map_any(),
+                    Sparkline::default().map(|counter: &Counter| &counter.history),
+                    Button::label_msg("&Undo", Undo)
+                        .map_any()
+                        .on_update(|cx, _, counter: &Counter| cx.set_disabled(!counter.undo.can_undo())),
+                ],
+                self.extra,
+                row! [
+                    Button::label_msg("&Add counter", AddCounter).map_any(),
+                    Button::label_msg("Open &mirror", OpenMirror).map_any(),
+                ],
+            ];
+        }]
+        struct {
+            core: widget_core!(),
+            #[widget(&())] extra: Column<Box<dyn Widget<Data = ()>>> = Column::new(vec![]),
+            /// Identifiers of rows in `extra`
+            ids: Vec<u32>,
+            next_id: u32,
+        }
+        impl Events for Self {
+            type Data = Counter;
+
+            fn handle_event(&mut self, cx: &mut EventCx, _: &Counter, event: Event) -> IsUsed {
+                // Keys not used by the focused widget (if any) arrive here.
+                // Changes are pushed to the shared data.
+                match event {
+                    Event::Command(Command::Up, _) => cx.push(Change(1)),
+                    Event::Command(Command::Down, _) => cx.push(Change(-1)),
+                    Event::Command(Command::Undo, _) => cx.push(Undo),
+                    Event::Key(event, _) if event.state == ElementState::Pressed => {
+                        match event.logical_key.as_ref() {
+                            Key::Character("+") => cx.push(Change(1)),
+                            Key::Character("-") => cx.push(Change(-1)),
+                            _ => return IsUsed::Unused,
+                        }
+                    }
+                    _ => return IsUsed::Unused,
+                }
+                IsUsed::Used
+            }
+
+            fn handle_messages(&mut self, cx: &mut EventCx, _: &Counter) {
+                if let Some(activated) = cx.try_pop::<NumberActivated<()>>() {
+                    // The count is set only on Enter; other edits are ignored
+                    cx.push(Set(activated.value as i32));
+                } else if cx.try_pop::<NumberEdited<()>>().is_some() {
+                } else if let Some(AddCounter) = cx.try_pop() {
+                    let id = self.next_id;
+                    self.next_id += 1;
+                    self.extra.push(&mut cx.config_cx(), &(), extra_counter(id));
+                    self.ids.push(id);
+                } else if let Some(RemoveCounter(id)) = cx.try_pop() {
+                    if let Some(index) = self.ids.iter().position(|x| *x == id) {
+                        self.ids.remove(index);
+                        self.extra.remove(cx, index);
+                    }
+                } else if let Some(OpenMirror) = cx.try_pop() {
+                    cx.add_window::<Shared>(demo_window(ui(), MENU, "Counter (mirror)"));
+                }
+            }
+        }
+    };
+    ui.map(|data: &Shared| &data.counter)
+}
+
+/// Menu actions: undo applies to the shared count, so is handled by [`Shared`]
+pub const MENU: MenuSpec = MenuSpec {
+    undo: Some(|cx| cx.push(Undo)),
+    shortcuts: &[
+        Shortcut::new("Up, +", "Increment the count"),
+        Shortcut::new("Down, -", "Decrement the count"),
+        Shortcut::new("Enter", "Set the count (in the count field)"),
+        Shortcut::new("Ctrl+Z, Alt+U", "Undo"),
+        Shortcut::new("Alt+A", "Add a counter"),
+        Shortcut::new("Alt+M", "Open a mirror window"),
+    ],
+    ..MenuSpec::NONE
+};
+
+/// Title of the demo's window
+pub const WINDOW_TITLE: &str = "Counter";
+
+/// Construct the demo in its own window
+pub fn window() -> Window<Shared> {
+    demo_window(ui(), MENU, WINDOW_TITLE)
 }
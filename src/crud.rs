@@ -4,31 +4,115 @@
 //     https://www.apache.org/licenses/LICENSE-2.0
 
 //! Create Read Update Delete
+//!
+//! Entries are saved as JSON after each change and loaded at start-up. The
+//! file is `crud.json` in the platform's data directory, unless set in the
+//! config:
+//! ```toml
+//! [demos.crud]
+//! path = "/path/to/names.json"
+//! ```
+//!
+//! The list may be filtered by surname prefix (as specified by 7GUIs), by
+//! substring or fuzzily. Matches keep the order of the entries, since kas's
+//! [`FilterList`] does: fuzzy matches are not ranked by score.
 
+use chrono::NaiveDate;
+use kas::event::{Command, FocusSource, MouseButton, PressSource};
 use kas::prelude::*;
+use kas::text::format::Markdown;
 use kas::view::filter::{
-    ContainsCaseInsensitive, Filter, FilterList, KeystrokeGuard, SetFilter, UnsafeFilteredList,
+    ContainsCaseInsensitive, Filter, FilterList, FilterValue, KeystrokeGuard, SetFilter,
+    UnsafeFilteredList,
 };
 use kas::view::{Driver, ListView, SelectionMode, SelectionMsg};
 use kas::widgets::edit::{EditBox, EditField, EditGuard};
-use kas::widgets::{AccessLabel, Button, Frame, NavFrame, ScrollBars, Text};
+use kas::widgets::{
+    AccessLabel, Button, Column, Frame, NavFrame, Popup, RadioButton, Row, ScrollBars, Text,
+};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Instant;
+use std::{fmt, io};
 
-#[derive(Clone, Debug)]
+use crate::clipboard;
+use crate::config::Config;
+use crate::data::Shared;
+use crate::demos::demo_window;
+use crate::menu::MenuSpec;
+use crate::palette::fuzzy_score;
+use crate::shortcuts::Shortcut;
+use crate::snapshot;
+use crate::status::{Status, StatusBar};
+use crate::toast::Toast;
+use crate::widgets::{format_date, parse_date};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Entry {
     first: String,
     last: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    /// Date of birth
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    birth: Option<NaiveDate>,
 }
 impl Entry {
     pub fn new<S: ToString, T: ToString>(last: T, first: S) -> Self {
         Entry {
             first: first.to_string(),
             last: last.to_string(),
+            email: None,
+            birth: None,
         }
     }
+    /// Construct from text fields, or say why they are invalid
+    ///
+    /// Fields are trimmed. The first name may be empty, as may `email` and
+    /// `birth` (in [`DATE_FORMAT`](crate::widgets::DATE_FORMAT)).
+    pub fn from_fields(last: &str, first: &str, email: &str, birth: &str) -> Result<Self, String> {
+        let last = last.trim();
+        if last.is_empty() {
+            return Err("Enter a surname".to_string());
+        }
+        let email = email.trim();
+        if !Field::Email.is_valid(email) {
+            return Err(format!("Invalid email address: {email}"));
+        }
+        let birth = match birth.trim() {
+            "" => None,
+            text => match parse_date(text) {
+                Ok(date) => Some(date),
+                Err(err) => return Err(format!("Invalid date of birth: {err}")),
+            },
+        };
+        let mut entry = Entry::new(last, first.trim());
+        entry.email = Some(email.to_string()).filter(|email| !email.is_empty());
+        entry.birth = birth;
+        Ok(entry)
+    }
+
     pub fn format(_: &ConfigCx, entry: &Entry) -> String {
-        format!("{}, {}", entry.last, entry.first)
+        entry.to_string()
+    }
+}
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}", self.last, self.first)?;
+        if let Some(email) = self.email.as_ref() {
+            write!(f, " <{email}>")?;
+        }
+        if let Some(birth) = self.birth {
+            write!(f, " (born {})", format_date(birth))?;
+        }
+        Ok(())
     }
 }
+/// Filter: either name contains the text, ignoring case
 impl Filter<Entry> for ContainsCaseInsensitive {
     fn matches(&self, item: &Entry) -> bool {
         Filter::<&str>::matches(self, &item.first.as_str())
@@ -36,32 +120,616 @@ impl Filter<Entry> for ContainsCaseInsensitive {
     }
 }
 
+/// Filter: the surname starts with the text, ignoring case (as specified by 7GUIs)
+#[derive(Clone, Debug, Default)]
+struct SurnamePrefix(String);
+impl FilterValue for SurnamePrefix {
+    type Value = String;
+
+    fn set_filter(&mut self, value: String) {
+        self.0 = value.to_lowercase();
+    }
+}
+impl Filter<Entry> for SurnamePrefix {
+    fn matches(&self, item: &Entry) -> bool {
+        item.last.to_lowercase().starts_with(&self.0)
+    }
+}
+
+/// Filter: each word of the text fuzzily matches the surname, first name or email
+///
+/// Words may match in any order, so "mx mstr" finds "Mustermann, Max".
+#[derive(Clone, Debug, Default)]
+struct Fuzzy(Vec<String>);
+impl FilterValue for Fuzzy {
+    type Value = String;
+
+    fn set_filter(&mut self, value: String) {
+        self.0 = value.split_whitespace().map(str::to_string).collect();
+    }
+}
+impl Filter<Entry> for Fuzzy {
+    fn matches(&self, item: &Entry) -> bool {
+        let email = item.email.as_deref().unwrap_or_default();
+        let fields = [item.last.as_str(), item.first.as_str(), email];
+        let word_matches = |word: &String| fields.iter().any(|f| fuzzy_score(word, f).is_some());
+        self.0.iter().all(word_matches)
+    }
+}
+
+/// Which filter matches entries
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum FilterMode {
+    #[default]
+    SurnamePrefix,
+    /// Either name contains the text
+    Substring,
+    Fuzzy,
+}
+
+/// A change to [`EntryFilter`]
+#[derive(Clone, Debug)]
+enum FilterChange {
+    Text(String),
+    Mode(FilterMode),
+}
+
+/// The filter of each [`FilterMode`], of which one is used
+#[derive(Clone, Debug)]
+struct EntryFilter {
+    mode: FilterMode,
+    /// The text, as entered
+    text: String,
+    prefix: SurnamePrefix,
+    substring: ContainsCaseInsensitive,
+    fuzzy: Fuzzy,
+}
+impl EntryFilter {
+    fn new() -> Self {
+        EntryFilter {
+            mode: FilterMode::default(),
+            text: String::new(),
+            prefix: SurnamePrefix::default(),
+            substring: ContainsCaseInsensitive::new(),
+            fuzzy: Fuzzy::default(),
+        }
+    }
+
+    /// The characters of an entry's surname, first name and email, each
+    /// with whether the filter matched it
+    fn marks(&self, item: &Entry) -> [(Vec<char>, Vec<bool>); 3] {
+        let email = item.email.as_deref().unwrap_or_default();
+        let fields = [item.last.as_str(), item.first.as_str(), email];
+        let mut marks = fields.map(|field| {
+            let chars: Vec<char> = field.chars().collect();
+            let marked = vec![false; chars.len()];
+            (chars, marked)
+        });
+        let query: Vec<char> = self.text.chars().collect();
+        match self.mode {
+            FilterMode::SurnamePrefix => {
+                let (chars, marked) = &mut marks[0];
+                if find_ignore_case(chars, &query) == Some(0) {
+                    marked[..query.len()].fill(true);
+                }
+            }
+            FilterMode::Substring => {
+                for (chars, marked) in &mut marks[..2] {
+                    if let Some(start) = find_ignore_case(chars, &query) {
+                        marked[start..start + query.len()].fill(true);
+                    }
+                }
+            }
+            FilterMode::Fuzzy => {
+                for word in &self.fuzzy.0 {
+                    // The field scoring best, preferring the first
+                    let scores = (0..3)
+                        .rev()
+                        .filter_map(|i| Some((fuzzy_score(word, fields[i])?, i)));
+                    let Some((_, i)) = scores.max_by_key(|(score, _)| *score) else {
+                        continue;
+                    };
+                    let word: Vec<char> = word.chars().collect();
+                    let (chars, marked) = &mut marks[i];
+                    for index in fuzzy_positions(&word, chars).unwrap_or_default() {
+                        marked[index] = true;
+                    }
+                }
+            }
+        }
+        marks
+    }
+
+    /// True if applying `change` would leave the filter as it is
+    fn is_unchanged_by(&self, change: &FilterChange) -> bool {
+        match change {
+            FilterChange::Text(text) => *text == self.text,
+            FilterChange::Mode(mode) => *mode == self.mode,
+        }
+    }
+
+    /// `item` as displayed, with the characters matched by the filter in bold
+    fn highlight(&self, item: &Entry) -> Markdown {
+        let [(last, last_marks), (first, first_marks), (email, email_marks)] = self.marks(item);
+        let mut md = String::new();
+        push_marked(&mut md, &last, &last_marks);
+        md += ", ";
+        push_marked(&mut md, &first, &first_marks);
+        if item.email.is_some() {
+            md += " \\<";
+            push_marked(&mut md, &email, &email_marks);
+            md += "\\>";
+        }
+        if let Some(birth) = item.birth {
+            md += &format!(" \\(born {}\\)", format_date(birth));
+        }
+        Markdown::new(&md).unwrap_or_else(|_| {
+            let plain: Vec<char> = item.to_string().chars().collect();
+            let mut md = String::new();
+            push_marked(&mut md, &plain, &[]);
+            Markdown::new(&md).unwrap_or_default()
+        })
+    }
+}
+
+/// True if `a` and `b` are equal, ignoring case
+fn eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Index of the first occurrence of non-empty `query` in `text`, ignoring case
+fn find_ignore_case(text: &[char], query: &[char]) -> Option<usize> {
+    if query.is_empty() || query.len() > text.len() {
+        return None;
+    }
+    text.windows(query.len()).position(|window| {
+        let mut pairs = window.iter().zip(query);
+        pairs.all(|(a, b)| eq_ignore_case(*a, *b))
+    })
+}
+
+/// Indices in `text` of the characters of `word`, matched in order as by [`fuzzy_score`]
+fn fuzzy_positions(word: &[char], text: &[char]) -> Option<Vec<usize>> {
+    let mut positions = Vec::with_capacity(word.len());
+    let mut start = 0;
+    for c in word {
+        let index = start + text[start..].iter().position(|t| eq_ignore_case(*t, *c))?;
+        positions.push(index);
+        start = index + 1;
+    }
+    Some(positions)
+}
+
+/// Append `chars` to Markdown `md`, escaped, with runs given by `marks` in bold
+///
+/// Characters beyond the end of `marks` are not marked.
+fn push_marked(md: &mut String, chars: &[char], marks: &[bool]) {
+    let mut bold = false;
+    for (i, c) in chars.iter().enumerate() {
+        let marked = marks.get(i).copied().unwrap_or(false);
+        if marked != bold {
+            md.push_str("**");
+            bold = marked;
+        }
+        if c.is_ascii_punctuation() {
+            md.push('\\');
+        }
+        md.push(*c);
+    }
+    if bold {
+        md.push_str("**");
+    }
+}
+
+impl FilterValue for EntryFilter {
+    type Value = FilterChange;
+
+    fn set_filter(&mut self, change: FilterChange) {
+        match change {
+            FilterChange::Text(text) => {
+                self.text = text.clone();
+                self.prefix.set_filter(text.clone());
+                self.substring.set_filter(text.clone());
+                self.fuzzy.set_filter(text);
+            }
+            FilterChange::Mode(mode) => self.mode = mode,
+        }
+    }
+}
+impl Filter<Entry> for EntryFilter {
+    fn matches(&self, item: &Entry) -> bool {
+        match self.mode {
+            FilterMode::SurnamePrefix => self.prefix.matches(item),
+            FilterMode::Substring => self.substring.matches(item),
+            FilterMode::Fuzzy => self.fuzzy.matches(item),
+        }
+    }
+}
+
+/// Select the filter mode
+#[derive(Clone, Debug)]
+struct SetFilterMode(FilterMode);
+
+/// A radio button selecting filter `mode`
+fn mode_button(label: &str, mode: FilterMode) -> RadioButton<FilterMode> {
+    let active = move |_: &ConfigCx, active: &FilterMode| *active == mode;
+    RadioButton::new_msg(label.to_string(), active, SetFilterMode(mode))
+}
+
+/// Entries used when there is no saved database
+fn default_entries() -> Vec<Entry> {
+    vec![
+        Entry::new("Emil", "Hans"),
+        Entry::new("Mustermann", "Max"),
+        Entry::new("Tisch", "Roman"),
+    ]
+}
+
+/// Path of the database: option `path` of demo `crud`, else a default
+fn database_path(config: &Config) -> Option<PathBuf> {
+    let path = config.demo_option::<PathBuf>("crud", "path");
+    path.or_else(|| Some(dirs::data_dir()?.join("kas-7guis").join("crud.json")))
+}
+
+/// Load entries saved by [`save_entries`], with a warning on failure
+///
+/// A missing file gives the default entries. So does a file which cannot be
+/// read or parsed; such a file is renamed with extension `.corrupt` (when
+/// possible), so that saving does not overwrite it.
+fn load_entries(path: &Path) -> (Vec<Entry>, Option<String>) {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return (default_entries(), None),
+        Err(err) => {
+            let warning = format!("Failed to read {}: {err}", path.display());
+            return (default_entries(), Some(warning));
+        }
+    };
+    match serde_json::from_str(&text) {
+        Ok(entries) => (entries, None),
+        Err(err) => {
+            let mut warning = format!("Failed to parse {}: {err}", path.display());
+            let aside = path.with_extension("json.corrupt");
+            if std::fs::rename(path, &aside).is_ok() {
+                warning += &format!(" (moved to {})", aside.display());
+            }
+            (default_entries(), Some(warning))
+        }
+    }
+}
+
+/// Save `entries` to `path` as JSON, creating its directory if needed
+fn save_entries(path: &Path, entries: &[Entry]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let text = serde_json::to_string_pretty(entries)?;
+    std::fs::write(path, text)
+}
+
+/// Format of files for import and export
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Rows of surname, first name, email, date of birth
+    Csv,
+    VCard,
+}
+
+impl Format {
+    /// vCard for extensions `vcf` and `vcard`, otherwise CSV
+    pub fn of(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+        match ext.to_ascii_lowercase().as_str() {
+            "vcf" | "vcard" => Format::VCard,
+            _ => Format::Csv,
+        }
+    }
+}
+
+/// Entries read from a file, and the number of invalid records skipped
+#[derive(Debug, Default)]
+pub struct Imported {
+    pub entries: Vec<Entry>,
+    pub skipped: usize,
+}
+
+impl Imported {
+    /// Add an entry from its fields, or skip it when invalid
+    fn add(&mut self, record: usize, fields: [&str; 4]) {
+        let [last, first, email, birth] = fields;
+        match Entry::from_fields(last, first, email, birth) {
+            Ok(entry) => self.entries.push(entry),
+            Err(problem) => {
+                log::warn!("Skipped record {record}: {problem}");
+                self.skipped += 1;
+            }
+        }
+    }
+}
+
+/// Header row written by [`to_csv`], and skipped by [`parse_csv`]
+const CSV_HEADER: [&str; 4] = ["surname", "first name", "email", "born"];
+
+/// Read entries from CSV
+///
+/// Each row is a surname, then optionally a first name, email and date of
+/// birth; any further fields are ignored. A first row starting with
+/// "surname" is taken as a header.
+pub fn parse_csv(text: &str) -> Result<Imported, csv::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut imported = Imported::default();
+    for (index, record) in reader.records().enumerate() {
+        let record = record?;
+        let field = |i| record.get(i).unwrap_or_default();
+        if index == 0 && field(0).trim().eq_ignore_ascii_case(CSV_HEADER[0]) {
+            continue;
+        }
+        imported.add(index + 1, [field(0), field(1), field(2), field(3)]);
+    }
+    Ok(imported)
+}
+
+/// Write entries as CSV, with a header row
+pub fn to_csv(entries: &[Entry]) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(CSV_HEADER)?;
+    for entry in entries {
+        let birth = entry.birth.map(format_date).unwrap_or_default();
+        let email = entry.email.as_deref().unwrap_or_default();
+        writer.write_record([
+            entry.last.as_str(),
+            entry.first.as_str(),
+            email,
+            birth.as_str(),
+        ])?;
+    }
+    let bytes = writer.into_inner().map_err(|err| err.into_error())?;
+    Ok(String::from_utf8(bytes).expect("written from strings"))
+}
+
+/// Split a vCard value at unescaped `;`, unescaping each component
+fn split_vcard_value(value: &str) -> Vec<String> {
+    let mut components = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        let last = components.last_mut().unwrap();
+        match c {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => last.push('\n'),
+                Some(c) => last.push(c),
+                None => (),
+            },
+            ';' => components.push(String::new()),
+            c => last.push(c),
+        }
+    }
+    components
+}
+
+/// Escape a vCard text value
+fn escape_vcard(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Read entries from vCards, using properties `N`, `EMAIL` and `BDAY`
+///
+/// Cards without a surname or with an invalid email or birthday are skipped.
+/// Of several `EMAIL` properties, the first is used.
+pub fn parse_vcards(text: &str) -> Imported {
+    // A line starting with white-space continues the previous line
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(prev)) => prev.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut imported = Imported::default();
+    let mut cards = 0;
+    // Surname, first name, email, date of birth
+    let mut card: Option<[String; 4]> = None;
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Ignore parameters and any group, as in "item1.EMAIL;TYPE=work"
+        let name = name.split(';').next().unwrap_or_default();
+        let name = name
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        match (name.as_str(), card.as_mut()) {
+            ("BEGIN", _) if value.trim().eq_ignore_ascii_case("VCARD") => {
+                card = Some(Default::default());
+            }
+            ("END", Some(_)) => {
+                cards += 1;
+                let fields = card.take().unwrap();
+                imported.add(cards, fields.each_ref().map(String::as_str));
+            }
+            ("N", Some(fields)) => {
+                let mut components = split_vcard_value(value).into_iter();
+                fields[0] = components.next().unwrap_or_default();
+                fields[1] = components.next().unwrap_or_default();
+            }
+            ("EMAIL", Some(fields)) if fields[2].is_empty() => {
+                fields[2] = split_vcard_value(value).join(";");
+            }
+            ("BDAY", Some(fields)) => {
+                let date = value.trim();
+                // Also accept the basic format, YYYYMMDD
+                fields[3] = match date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) {
+                    true => format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..]),
+                    false => date.to_string(),
+                };
+            }
+            _ => (),
+        }
+    }
+    imported
+}
+
+/// Write entries as vCards (version 3.0)
+pub fn to_vcards(entries: &[Entry]) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        let (last, first) = (escape_vcard(&entry.last), escape_vcard(&entry.first));
+        text += "BEGIN:VCARD\r\nVERSION:3.0\r\n";
+        text += &format!("N:{last};{first};;;\r\n");
+        text += &format!("FN:{}\r\n", format!("{first} {last}").trim());
+        if let Some(email) = entry.email.as_ref() {
+            text += &format!("EMAIL:{}\r\n", escape_vcard(email));
+        }
+        if let Some(birth) = entry.birth {
+            text += &format!("BDAY:{}\r\n", format_date(birth));
+        }
+        text += "END:VCARD\r\n";
+    }
+    text
+}
+
+/// Import entries from `path`, in the [`Format`] given by its extension
+pub fn import_file(path: &Path) -> io::Result<Imported> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(match Format::of(path) {
+        Format::Csv => parse_csv(&text)?,
+        Format::VCard => parse_vcards(&text),
+    })
+}
+
+/// Export entries to `path`, in the [`Format`] given by its extension
+pub fn export_file(path: &Path, entries: &[Entry]) -> io::Result<()> {
+    let text = match Format::of(path) {
+        Format::Csv => to_csv(entries)?,
+        Format::VCard => to_vcards(entries),
+    };
+    std::fs::write(path, text)
+}
+
+/// Import entries from the file named in the UI
+#[derive(Clone, Debug)]
+struct ImportFile;
+
+/// Export all entries to the file named in the UI
+#[derive(Clone, Debug)]
+struct ExportFile;
+
 #[derive(Clone, Debug)]
 enum Control {
     Create,
     Update,
     Delete,
+    /// Copy the selected entry to the clipboard
+    Copy,
+    /// Sort entries by this name; repeating reverses the order
+    Sort(SortKey),
+}
+
+/// The name by which to sort entries
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SortKey {
+    Surname,
+    FirstName,
+}
+
+/// Compare entries by `key`, then by the other name, ignoring case
+fn compare_entries(a: &Entry, b: &Entry, key: SortKey) -> Ordering {
+    let names = |entry: &Entry| {
+        let (primary, secondary) = match key {
+            SortKey::Surname => (&entry.last, &entry.first),
+            SortKey::FirstName => (&entry.first, &entry.last),
+        };
+        (primary.to_lowercase(), secondary.to_lowercase())
+    };
+    names(a).cmp(&names(b))
 }
 
+/// True if `text` looks like an email address: `local@domain.tld`, without spaces
+fn is_valid_email(text: &str) -> bool {
+    let Some((local, domain)) = text.split_once('@') else {
+        return false;
+    };
+    let labels_ok = domain.split('.').all(|label| !label.is_empty());
+    !local.is_empty()
+        && domain.contains('.')
+        && labels_ok
+        && !domain.contains('@')
+        && !text.contains(char::is_whitespace)
+}
+
+/// A field of [`Entry`], edited via [`FieldGuard`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    First,
+    Last,
+    /// Optional
+    Email,
+    /// Optional; in [`DATE_FORMAT`](crate::widgets::DATE_FORMAT)
+    Birth,
+}
+
+impl Field {
+    /// The text of this field of `entry`
+    fn text(self, entry: &Entry) -> String {
+        match self {
+            Field::First => entry.first.clone(),
+            Field::Last => entry.last.clone(),
+            Field::Email => entry.email.clone().unwrap_or_default(),
+            Field::Birth => entry.birth.map(format_date).unwrap_or_default(),
+        }
+    }
+
+    /// True if `text` is acceptable; names are marked when empty
+    fn is_valid(self, text: &str) -> bool {
+        let text = text.trim();
+        match self {
+            Field::First | Field::Last => !text.is_empty(),
+            Field::Email => text.is_empty() || is_valid_email(text),
+            Field::Birth => text.is_empty() || parse_date(text).is_ok(),
+        }
+    }
+}
+
+/// Shows a field of the selected entry; the error state marks invalid input
 #[derive(Clone, Debug)]
-struct NameGuard {
-    is_last: bool,
+struct FieldGuard {
+    field: Field,
 }
-impl EditGuard for NameGuard {
+impl EditGuard for FieldGuard {
     type Data = Option<Entry>;
 
     fn update(edit: &mut EditField<Self>, cx: &mut ConfigCx, data: &Self::Data) {
         let mut act = Action::empty();
         if let Some(entry) = data.as_ref() {
-            let name = match edit.guard.is_last {
-                false => &entry.first,
-                true => &entry.last,
-            };
-            act = edit.set_str(name);
+            act = edit.set_string(edit.guard.field.text(entry));
         }
-        act |= edit.set_error_state(edit.get_str().is_empty());
+        act |= edit.set_error_state(!edit.guard.field.is_valid(edit.get_str()));
         cx.action(edit, act);
     }
+
+    fn edit(edit: &mut EditField<Self>, cx: &mut EventCx, _: &Self::Data) {
+        let act = edit.set_error_state(!edit.guard.field.is_valid(edit.get_str()));
+        cx.action(edit.id(), act);
+    }
 }
 
 impl_scope! {
@@ -73,22 +741,267 @@ impl_scope! {
             (1, 0) => self.firstname,
             (0, 1) => "Surname:",
             (1, 1) => self.surname,
+            (0, 2) => "Email:",
+            (1, 2) => self.email,
+            (0, 3) => "Born (YYYY-MM-DD):",
+            (1, 3) => self.birth,
         };
     }]
     struct Editor {
         core: widget_core!(),
-        #[widget] firstname: EditBox<NameGuard> = EditBox::new(NameGuard { is_last: false }),
-        #[widget] surname: EditBox<NameGuard> = EditBox::new(NameGuard { is_last: true }),
+        #[widget] firstname: EditBox<FieldGuard> = EditBox::new(FieldGuard { field: Field::First }),
+        #[widget] surname: EditBox<FieldGuard> = EditBox::new(FieldGuard { field: Field::Last }),
+        #[widget] email: EditBox<FieldGuard> = EditBox::new(FieldGuard { field: Field::Email }),
+        #[widget] birth: EditBox<FieldGuard> = EditBox::new(FieldGuard { field: Field::Birth }),
     }
     impl Self {
-        fn make_item(&self) -> Option<Entry> {
-            let last = self.surname.get_string();
-            if last.is_empty() {
+        /// The entry given by the fields, or why they are invalid
+        fn make_item(&self) -> Result<Entry, String> {
+            Entry::from_fields(
+                self.surname.get_str(),
+                self.firstname.get_str(),
+                self.email.get_str(),
+                self.birth.get_str(),
+            )
+        }
+    }
+}
+
+/// Rename entry `.0` from inline text `.1`, "Surname, First name"
+#[derive(Debug)]
+struct RenameEntry(usize, String);
+
+/// Open the context menu of entry `.0`, beside row widget `.1`
+#[derive(Debug)]
+struct OpenRowMenu(usize, Id);
+
+/// An action of a list row's context menu
+#[derive(Clone, Debug)]
+enum RowAction {
+    /// Select only this entry, loading it into the editor
+    Edit,
+    /// Insert a copy after this entry
+    Duplicate,
+    Delete,
+}
+
+/// Commit an inline edit
+#[derive(Debug)]
+struct CommitRename;
+
+/// End an inline edit without committing
+#[derive(Debug)]
+struct CancelRename;
+
+#[derive(Clone, Debug)]
+struct RenameGuard;
+impl EditGuard for RenameGuard {
+    type Data = Entry;
+
+    fn activate(_: &mut EditField<Self>, cx: &mut EventCx, _: &Entry) -> IsUsed {
+        cx.push(CommitRename);
+        IsUsed::Used
+    }
+
+    fn focus_lost(_: &mut EditField<Self>, cx: &mut EventCx, _: &Entry) {
+        cx.push(CancelRename);
+    }
+}
+
+/// The list's filter, shared with its rows to highlight matches
+type SharedFilter = Rc<RefCell<EntryFilter>>;
+
+impl_scope! {
+    /// A row of the entry list, showing entry `key`
+    ///
+    /// A double-click edits the entry's name in place; Enter commits while
+    /// Escape or moving focus elsewhere cancels. A right-click opens the
+    /// row's context menu.
+    ///
+    /// The list view reuses rows for other entries (e.g. when scrolled or
+    /// filtered), calling [`Self::set_key`] via [`ListGuard`].
+    #[widget]
+    pub struct EntryRow {
+        core: widget_core!(),
+        key: usize,
+        editing: bool,
+        /// Matches of the filter are shown in bold
+        #[widget] text: Text<Entry, Markdown>,
+        #[widget] edit: EditBox<RenameGuard>,
+    }
+    impl Self {
+        fn new(key: usize, filter: SharedFilter) -> Self {
+            EntryRow {
+                core: Default::default(),
+                key,
+                editing: false,
+                text: Text::new(move |_, entry: &Entry| filter.borrow().highlight(entry)),
+                edit: EditBox::new(RenameGuard),
+            }
+        }
+
+        /// Start or stop editing; the edit field is disabled while hidden
+        fn set_editing(&mut self, cx: &mut EventCx, entry: &Entry, editing: bool) {
+            self.editing = editing;
+            cx.set_disabled(self.edit.id(), !editing);
+            if editing {
+                let action = self.edit.set_string(format!("{}, {}", entry.last, entry.first));
+                cx.action(self.edit.id(), action);
+                cx.next_nav_focus(Some(self.edit.id()), false, FocusSource::Pointer);
+            }
+            cx.redraw(self);
+        }
+
+        /// The index of the entry shown
+        pub fn key(&self) -> usize {
+            self.key
+        }
+
+        /// Show entry `key` instead
+        ///
+        /// A rename in progress is abandoned, since its text belongs to the
+        /// previous entry. (The edit field is disabled again when the list
+        /// view re-configures the row.)
+        fn set_key(&mut self, key: usize) {
+            if key != self.key {
+                self.editing = false;
+            }
+            self.key = key;
+        }
+    }
+    impl Layout for Self {
+        fn size_rules(&mut self, sizer: SizeCx, axis: AxisInfo) -> SizeRules {
+            // Sized for either, so that rows do not move when editing starts
+            let rules = self.text.size_rules(sizer.re(), axis);
+            rules.max(self.edit.size_rules(sizer, axis))
+        }
+
+        fn set_rect(&mut self, cx: &mut ConfigCx, rect: Rect) {
+            self.core.rect = rect;
+            self.text.set_rect(cx, rect);
+            self.edit.set_rect(cx, rect);
+        }
+
+        fn find_id(&mut self, coord: Coord) -> Option<Id> {
+            if !self.rect().contains(coord) {
                 return None;
             }
-            Some(Entry::new(last, self.firstname.get_string()))
+            // Presses on the text reach this row, then the list
+            let found = match self.editing {
+                true => self.edit.find_id(coord),
+                false => None,
+            };
+            found.or_else(|| Some(self.id()))
+        }
+
+        fn draw(&mut self, mut draw: DrawCx) {
+            match self.editing {
+                true => draw.recurse(&mut self.edit),
+                false => draw.recurse(&mut self.text),
+            }
         }
     }
+    impl Events for Self {
+        type Data = Entry;
+
+        fn configure(&mut self, cx: &mut ConfigCx) {
+            cx.set_disabled(self.edit.id(), true);
+        }
+
+        fn handle_event(&mut self, cx: &mut EventCx, entry: &Entry, event: Event) -> IsUsed {
+            match event {
+                Event::PressStart { press }
+                    if !self.editing
+                        && matches!(press.source, PressSource::Mouse(MouseButton::Left, 2)) =>
+                {
+                    self.set_editing(cx, entry, true);
+                    IsUsed::Used
+                }
+                Event::PressStart { press }
+                    if matches!(press.source, PressSource::Mouse(MouseButton::Right, _)) =>
+                {
+                    cx.push(OpenRowMenu(self.key(), self.id()));
+                    IsUsed::Used
+                }
+                Event::Command(Command::Escape, _) if self.editing => {
+                    self.set_editing(cx, entry, false);
+                    IsUsed::Used
+                }
+                _ => IsUsed::Unused,
+            }
+        }
+
+        fn handle_messages(&mut self, cx: &mut EventCx, entry: &Entry) {
+            if let Some(CommitRename) = cx.try_pop() {
+                cx.push(RenameEntry(self.key, self.edit.get_string()));
+                self.set_editing(cx, entry, false);
+            } else if let Some(CancelRename) = cx.try_pop() {
+                if self.editing {
+                    self.set_editing(cx, entry, false);
+                }
+            }
+        }
+    }
+}
+
+/// Answer to the [`Confirmation`] question
+#[derive(Clone, Debug)]
+enum Confirm {
+    Yes,
+    Cancel,
+}
+
+/// A change made only once confirmed
+#[derive(Debug)]
+enum Pending {
+    Create(Entry),
+    /// Replace the entry at this index
+    Update(usize, Entry),
+    /// Delete the selected entries
+    Delete,
+}
+
+/// The entry targeted by a row's context menu action
+///
+/// This is `menu_key`, the key of the row on which the menu was opened, if
+/// the entry still exists.
+pub fn menu_entry(menu_key: Option<usize>, entries: &[Entry]) -> Option<usize> {
+    menu_key.filter(|key| *key < entries.len())
+}
+
+/// Remove the entries at indices `selection`
+pub fn remove_entries(entries: &mut Vec<Entry>, selection: &BTreeSet<usize>) {
+    for index in selection.iter().rev() {
+        entries.remove(*index);
+    }
+}
+
+/// True if `a` and `b` have the same surname and first name, ignoring case
+fn same_name(a: &Entry, b: &Entry) -> bool {
+    a.last.to_lowercase() == b.last.to_lowercase()
+        && a.first.to_lowercase() == b.first.to_lowercase()
+}
+
+impl_scope! {
+    /// Asks whether to make a [`Pending`] change; the data is the question
+    #[impl_default]
+    #[widget {
+        layout = column! [
+            self.question,
+            row! [
+                Button::label_msg("&Yes", Confirm::Yes).map_any(),
+                Button::label_msg("Cancel", Confirm::Cancel).map_any(),
+            ],
+        ];
+    }]
+    struct Confirmation {
+        core: widget_core!(),
+        #[widget] question: Text<String, String> =
+            Text::new(|_, question: &String| question.clone()),
+    }
+    impl Events for Self {
+        type Data = String;
+    }
 }
 
 impl_scope! {
@@ -98,113 +1011,535 @@ impl_scope! {
             Button::label_msg("Create", Control::Create).map_any(),
             self.update,
             self.delete,
+            self.copy,
+            Button::label_msg("Sort by surname", Control::Sort(SortKey::Surname)).map_any(),
+            Button::label_msg("Sort by first name", Control::Sort(SortKey::FirstName)).map_any(),
         ];
     }]
     struct Controls {
         core: widget_core!(),
         #[widget(&())] update: Button<AccessLabel> = Button::label_msg("Update", Control::Update),
         #[widget(&())] delete: Button<AccessLabel> = Button::label_msg("Delete", Control::Delete),
+        #[widget(&())] copy: Button<AccessLabel> = Button::label_msg("&Copy", Control::Copy),
     }
     impl Events for Self {
-        type Data = bool;
+        /// The number of selected entries
+        type Data = usize;
 
-        fn update(&mut self, cx: &mut ConfigCx, any_selected: &bool) {
+        fn update(&mut self, cx: &mut ConfigCx, num_selected: &usize) {
             if self.update.id_ref().is_valid() {
-                let disable = !any_selected;
-                cx.set_disabled(self.update.id(), disable);
+                cx.set_disabled(self.update.id(), *num_selected != 1);
+                let disable = *num_selected == 0;
                 cx.set_disabled(self.delete.id(), disable);
+                cx.set_disabled(self.copy.id(), disable);
             }
         }
     }
 }
 
-pub fn window() -> Window<()> {
-    struct ListGuard;
-    type FilteredList = UnsafeFilteredList<Vec<Entry>>;
-    impl Driver<Entry, FilteredList> for ListGuard {
-        type Widget = NavFrame<Text<Entry, String>>;
-        fn make(&mut self, _: &usize) -> Self::Widget {
-            NavFrame::new(Text::new(Entry::format))
+/// Syllables of names made by [`generated_entries`]
+const SYLLABLES: [&str; 16] = [
+    "ba", "ber", "do", "fen", "gar", "hal", "ki", "lin", "mo", "ner", "ri", "sa", "tor", "vel",
+    "win", "zu",
+];
+
+/// `n` entries with names made of pseudo-random syllables, in no order
+fn generated_entries(n: usize) -> Vec<Entry> {
+    let name = |mut x: u64, syllables: usize| {
+        let mut name = String::new();
+        for _ in 0..syllables {
+            name += SYLLABLES[(x % 16) as usize];
+            x /= 16;
         }
+        name[..1].to_uppercase() + &name[1..]
+    };
+    (0..n as u64)
+        .map(|i| {
+            let x = i.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            Entry::new(name(x >> 40, 3), name(x >> 16, 2))
+        })
+        .collect()
+}
+
+/// Construct a CRUD window over `n` generated entries, for profiling
+///
+/// These entries are neither loaded nor saved. Timings of generation and of
+/// each filter change are printed to stderr.
+pub fn large_window(n: usize) -> Window<Shared> {
+    let start = Instant::now();
+    let entries = generated_entries(n);
+    eprintln!("crud: generated {n} entries in {:?}", start.elapsed());
+    let title = format!("{WINDOW_TITLE} ({n} entries)");
+    demo_window(list_ui(Some(entries)), MENU, &title)
+}
+
+pub fn ui() -> impl Widget<Data = Shared> {
+    list_ui(None)
+}
+
+/// The filtered entries shown by the list
+type FilteredList = UnsafeFilteredList<Vec<Entry>>;
+
+/// Driver making the list's [`EntryRow`]s
+pub struct ListGuard(SharedFilter);
+
+impl Default for ListGuard {
+    fn default() -> Self {
+        ListGuard(SharedFilter::new(RefCell::new(EntryFilter::new())))
+    }
+}
+
+impl Driver<Entry, FilteredList> for ListGuard {
+    type Widget = NavFrame<EntryRow>;
+    fn make(&mut self, key: &usize) -> Self::Widget {
+        NavFrame::new(EntryRow::new(*key, self.0.clone()))
+    }
+    fn set_key(&mut self, widget: &mut Self::Widget, key: &usize) {
+        // Rows act on their key (e.g. from the context menu), so it must
+        // follow the entry shown
+        widget.inner.set_key(*key);
     }
-    let filter = ContainsCaseInsensitive::new();
+}
+
+/// Construct the UI over the saved entries, or over `generated` entries
+///
+/// With generated entries, filter changes are timed.
+fn list_ui(generated: Option<Vec<Entry>>) -> impl Widget<Data = Shared> {
+    let timed = generated.is_some();
+    let filter = EntryFilter::new();
     let guard = KeystrokeGuard;
     type MyListView = ListView<UnsafeFilteredList<Vec<Entry>>, ListGuard, kas::dir::Down>;
-    type MyFilterList = FilterList<Vec<Entry>, ContainsCaseInsensitive, MyListView>;
-    let list_view = MyListView::new(ListGuard).with_selection_mode(SelectionMode::Single);
+    type MyFilterList = FilterList<Vec<Entry>, EntryFilter, MyListView>;
+    let modes = vec![
+        mode_button("Surname prefix", FilterMode::SurnamePrefix),
+        mode_button("Substring", FilterMode::Substring),
+        mode_button("Fuzzy", FilterMode::Fuzzy),
+    ];
+    let shared_filter = SharedFilter::new(RefCell::new(filter.clone()));
+    let list_guard = ListGuard(shared_filter.clone());
+    let list_view = MyListView::new(list_guard).with_selection_mode(SelectionMode::Multiple);
+    let dir = dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_default();
+    let file_path = dir.join("contacts.csv").display().to_string();
 
     let ui = impl_anon! {
         #[widget {
             layout = grid! {
                 (0, 0) => "Filter:",
                 (1, 0) => self.filter,
+                (2..4, 0) => self.modes,
                 (0..2, 1..3) => self.list,
                 (3, 1) => self.editor,
                 (0..4, 3) => self.controls,
+                (0, 4) => "File:",
+                (1..3, 4) => self.file_path,
+                (3, 4) => row! [
+                    Button::label_msg("&Import", ImportFile).map_any(),
+                    Button::label_msg("&Export", ExportFile).map_any(),
+                ],
             };
         }]
         struct {
             core: widget_core!(),
             #[widget(&())] filter: EditBox<KeystrokeGuard> = EditBox::new(guard),
+            #[widget(&self.mode)] modes: Row<RadioButton<FilterMode>> = Row::new(modes),
+            mode: FilterMode,
+            /// A copy of the list's filter, used by rows
+            shared_filter: SharedFilter = shared_filter,
             #[widget(&self.entries)] list: Frame<ScrollBars<MyFilterList>> =
                 Frame::new(ScrollBars::new(FilterList::new(list_view, filter))),
             #[widget(&self.selected)] editor: Editor = Editor::default(),
-            #[widget(&self.selected.is_some())] controls: Controls = Controls::default(),
-            entries: Vec<Entry> = vec![
-                Entry::new("Emil", "Hans"),
-                Entry::new("Mustermann", "Max"),
-                Entry::new("Tisch", "Roman"),
-            ],
+            #[widget(&self.selection.len())] controls: Controls = Controls::default(),
+            /// Opened to confirm [`Self::pending`]
+            #[widget(&self.question)] confirm: Popup<Confirmation> =
+                Popup::new(Confirmation::default(), Direction::Up),
+            question: String,
+            pending: Option<Pending>,
+            /// Opened by right-clicking entry [`Self::menu_key`]
+            #[widget(&())] row_menu: Popup<Column<Button<AccessLabel>>> = Popup::new(
+                Column::new(vec![
+                    Button::label_msg("Edi&t", RowAction::Edit),
+                    Button::label_msg("D&uplicate", RowAction::Duplicate),
+                    Button::label_msg("De&lete", RowAction::Delete),
+                ]),
+                Direction::Down,
+            ),
+            menu_key: Option<usize>,
+            /// For import and export; see [`Format::of`]
+            #[widget(&())] file_path: EditBox = EditBox::text(file_path),
+            /// Loaded on the first update, from [`Self::path`], unless generated
+            entries: Vec<Entry> = generated.unwrap_or_default(),
+            /// Indices of selected entries, as in the list
+            selection: BTreeSet<usize>,
+            /// The entry to edit, when exactly one is selected
             selected: Option<Entry>,
+            /// Where entries are saved, if anywhere; see [`database_path`]
+            path: Option<PathBuf>,
+            loaded: bool = timed,
+            timed: bool = timed,
+            /// The last sort applied, and whether descending
+            sorted: Option<(SortKey, bool)>,
         }
         impl Self {
+            /// The selected entry's index, when exactly one is selected
             fn selected(&self) -> Option<usize> {
-                self.list.selected_iter().next().cloned()
+                match self.selection.len() {
+                    1 => self.selection.first().cloned(),
+                    _ => None,
+                }
+            }
+
+            /// Re-read the selection from the list
+            fn sync_selection(&mut self, cx: &mut EventCx, shared: &Shared) {
+                self.selection = self.list.selected_iter().cloned().collect();
+                self.selected = self.selected().and_then(|i| self.entries.get(i).cloned());
+                cx.update(self.as_node(shared));
+            }
+
+            /// Select only `indices`, then sync
+            fn select_only(
+                &mut self,
+                cx: &mut EventCx,
+                shared: &Shared,
+                indices: impl IntoIterator<Item = usize>,
+            ) {
+                let mut action = self.list.clear_selected();
+                for index in indices {
+                    action |= self.list.select(index);
+                }
+                cx.action(&self, action);
+                self.sync_selection(cx, shared);
+            }
+
+            /// Change the filter, timing it if `timed`
+            ///
+            /// A change to the current text or mode is ignored, since
+            /// refiltering would update every row for nothing.
+            fn set_filter(&mut self, cx: &mut EventCx, change: FilterChange) {
+                if self.shared_filter.borrow().is_unchanged_by(&change) {
+                    return;
+                }
+                let start = Instant::now();
+                self.shared_filter.borrow_mut().set_filter(change.clone());
+                self.list.set_filter(&mut cx.config_cx(), &self.entries, change);
+                if self.timed {
+                    let n = self.entries.len();
+                    eprintln!("crud: filtered {n} entries in {:?}", start.elapsed());
+                }
+            }
+
+            /// Apply a context menu action to entry `index`
+            fn row_action(
+                &mut self,
+                cx: &mut EventCx,
+                shared: &Shared,
+                index: usize,
+                action: RowAction,
+            ) {
+                match action {
+                    RowAction::Edit => self.select_only(cx, shared, [index]),
+                    RowAction::Duplicate => {
+                        let item = self.entries[index].clone();
+                        cx.push(Status::Message(format!("Duplicated {item}")));
+                        self.entries.insert(index + 1, item);
+                        self.select_only(cx, shared, [index + 1]);
+                        self.save(cx);
+                    }
+                    RowAction::Delete => {
+                        self.select_only(cx, shared, [index]);
+                        let question = format!("Delete {}?", self.entries[index]);
+                        self.ask(cx, question, Pending::Delete);
+                    }
+                }
+            }
+
+            /// Rename entry `index` from `text`, "Surname, First name"
+            ///
+            /// This is an update, like that of the editor, keeping the email
+            /// and date of birth. Text without a comma is all surname.
+            fn rename(&mut self, cx: &mut EventCx, shared: &Shared, index: usize, text: &str) {
+                let Some(entry) = self.entries.get(index) else {
+                    return;
+                };
+                let (last, first) = text.rsplit_once(',').unwrap_or((text, ""));
+                let email = entry.email.clone().unwrap_or_default();
+                let birth = entry.birth.map(format_date).unwrap_or_default();
+                match Entry::from_fields(last, first, &email, &birth) {
+                    Ok(item) => self.apply_checked(cx, shared, Pending::Update(index, item)),
+                    Err(problem) => cx.push(Status::Message(problem)),
+                }
+            }
+
+            /// Append entries imported from the file named by `file_path`
+            fn import(&mut self, cx: &mut EventCx, shared: &Shared) {
+                let path = PathBuf::from(self.file_path.get_string());
+                match import_file(&path) {
+                    Ok(Imported { entries, skipped }) => {
+                        let added = entries.len();
+                        let start = self.entries.len();
+                        self.entries.extend(entries);
+                        self.select_only(cx, shared, start..self.entries.len());
+                        let mut message =
+                            format!("Imported {added} entries from {}", path.display());
+                        if skipped > 0 {
+                            message += &format!("; skipped {skipped} invalid");
+                        }
+                        cx.push(Status::Message(message));
+                        if added > 0 {
+                            self.save(cx);
+                        }
+                    }
+                    Err(error) => cx.push(Toast(format!("Import failed: {error}"))),
+                }
+            }
+
+            /// Ask whether to make the `pending` change
+            fn ask(&mut self, cx: &mut EventCx, question: String, pending: Pending) {
+                self.question = question;
+                self.pending = Some(pending);
+                self.confirm.open(cx, &self.question, self.controls.id(), true);
+            }
+
+            /// An entry other than that at `except` with the same name as `entry`
+            fn find_duplicate(&self, entry: &Entry, except: Option<usize>) -> Option<&Entry> {
+                let mut others = self.entries.iter().enumerate();
+                let found = others.find(|(i, other)| Some(*i) != except && same_name(other, entry));
+                found.map(|(_, other)| other)
+            }
+
+            /// Make a change, first asking if it would duplicate a name
+            fn apply_checked(&mut self, cx: &mut EventCx, shared: &Shared, change: Pending) {
+                let (item, except, verb) = match &change {
+                    Pending::Create(item) => (item, None, "Create"),
+                    Pending::Update(index, item) => (item, Some(*index), "Update"),
+                    Pending::Delete => return self.apply(cx, shared, change),
+                };
+                match self.find_duplicate(item, except) {
+                    Some(other) => {
+                        let question = format!("{other} exists. {verb} anyway?");
+                        self.ask(cx, question, change);
+                    }
+                    None => self.apply(cx, shared, change),
+                }
+            }
+
+            /// Make a change, now confirmed if needed
+            fn apply(&mut self, cx: &mut EventCx, shared: &Shared, change: Pending) {
+                match change {
+                    Pending::Create(item) => {
+                        let index = self.entries.len();
+                        cx.push(Status::Message(format!("Created {item}")));
+                        self.entries.push(item);
+                        self.select_only(cx, shared, [index]);
+                    }
+                    Pending::Update(index, item) => {
+                        cx.push(Status::Message(format!("Updated {item}")));
+                        self.entries[index] = item;
+                        // Also refreshes the editor, after an inline rename
+                        self.sync_selection(cx, shared);
+                    }
+                    Pending::Delete => self.delete(cx, shared),
+                }
+                self.save(cx);
+            }
+
+            /// Delete all selected entries
+            fn delete(&mut self, cx: &mut EventCx, shared: &Shared) {
+                let Some(first) = self.selection.first().cloned() else {
+                    return;
+                };
+                let message = match self.selected() {
+                    Some(index) => format!("Deleted {}", self.entries[index]),
+                    None => format!("Deleted {} entries", self.selection.len()),
+                };
+                remove_entries(&mut self.entries, &self.selection);
+                cx.push(Status::Message(message));
+                // Select the entry following the first deleted, if any
+                let next = Some(first).filter(|i| *i < self.entries.len());
+                self.select_only(cx, shared, next);
+            }
+
+            /// Sort entries by `key`, descending if just sorted ascending by `key`
+            ///
+            /// Selected entries stay selected.
+            fn sort(&mut self, cx: &mut EventCx, shared: &Shared, key: SortKey) {
+                let descending = self.sorted == Some((key, false));
+                self.sorted = Some((key, descending));
+                let mut order: Vec<usize> = (0..self.entries.len()).collect();
+                order.sort_by(|a, b| {
+                    let ordering = compare_entries(&self.entries[*a], &self.entries[*b], key);
+                    if descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                });
+                self.entries = order.iter().map(|i| self.entries[*i].clone()).collect();
+                let selection = order
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, old)| self.selection.contains(old))
+                    .map(|(new, _)| new)
+                    .collect::<Vec<_>>();
+                self.select_only(cx, shared, selection);
+                let name = match key {
+                    SortKey::Surname => "surname",
+                    SortKey::FirstName => "first name",
+                };
+                let direction = if descending { "descending" } else { "ascending" };
+                cx.push(Status::Message(format!("Sorted by {name} ({direction})")));
+                self.save(cx);
+            }
+
+            /// Save entries after a change, reporting any error as a toast
+            fn save(&self, cx: &mut EventCx) {
+                let Some(path) = self.path.as_ref() else {
+                    return;
+                };
+                if let Err(err) = save_entries(path, &self.entries) {
+                    cx.push(Toast(format!("Failed to save {}: {err}", path.display())));
+                }
             }
         }
         impl Events for Self {
-            type Data = ();
+            type Data = Shared;
+
+            fn update(&mut self, cx: &mut ConfigCx, shared: &Shared) {
+                if self.loaded {
+                    return;
+                }
+                self.loaded = true;
+                self.path = database_path(&shared.config);
+                let Some(path) = self.path.as_ref() else {
+                    self.entries = default_entries();
+                    return;
+                };
+                let (entries, warning) = load_entries(path);
+                self.entries = entries;
+                if let Some(warning) = warning {
+                    log::warn!("{warning}");
+                    // Not handled here: this reaches the enclosing toasts
+                    cx.send(self.id(), Toast(warning));
+                }
+            }
 
-            fn handle_messages(&mut self, cx: &mut EventCx, _: &()) {
+            fn handle_event(&mut self, cx: &mut EventCx, _: &Shared, event: Event) -> IsUsed {
+                if snapshot::is_dump_event(cx, &event) {
+                    let state = serde_json::json!({
+                        "entries": self.entries,
+                        "selected": self.selection,
+                    });
+                    snapshot::dump(cx, "crud", &state);
+                    IsUsed::Used
+                } else {
+                    IsUsed::Unused
+                }
+            }
+
+            fn handle_messages(&mut self, cx: &mut EventCx, shared: &Shared) {
                 if let Some(SetFilter(value)) = cx.try_pop() {
-                    self.list.set_filter(&mut cx.config_cx(), &self.entries, value);
-                } else if let Some(SelectionMsg::Select(key)) = cx.try_pop() {
-                    self.selected = self.entries.get::<usize>(key).cloned();
-                    cx.update(self.as_node(&()));
-                } else if let Some(control) = cx.try_pop() {
-                    match control {
-                        Control::Create => {
-                            if let Some(item) = self.editor.make_item() {
-                                let index = self.entries.len();
-                                self.entries.push(item);
-                                let action = self.list.select(index);
-                                cx.action(&self, action);
-                                self.selected = self.entries.get(index).cloned();
-                                cx.update(self.as_node(&()));
-                            }
+                    self.set_filter(cx, FilterChange::Text(value));
+                } else if let Some(SetFilterMode(mode)) = cx.try_pop() {
+                    self.mode = mode;
+                    cx.update(self.modes.as_node(&self.mode));
+                    self.set_filter(cx, FilterChange::Mode(mode));
+                } else if let Some(OpenRowMenu(key, id)) = cx.try_pop() {
+                    self.menu_key = Some(key);
+                    self.row_menu.open(cx, &(), id, true);
+                } else if let Some(action) = cx.try_pop::<RowAction>() {
+                    self.row_menu.close(cx);
+                    if let Some(index) = menu_entry(self.menu_key.take(), &self.entries) {
+                        self.row_action(cx, shared, index, action);
+                    }
+                } else if let Some(RenameEntry(index, text)) = cx.try_pop() {
+                    self.rename(cx, shared, index, &text);
+                } else if let Some(ImportFile) = cx.try_pop() {
+                    self.import(cx, shared);
+                } else if let Some(ExportFile) = cx.try_pop() {
+                    let path = PathBuf::from(self.file_path.get_string());
+                    match export_file(&path, &self.entries) {
+                        Ok(()) => {
+                            let n = self.entries.len();
+                            let message = format!("Exported {n} entries to {}", path.display());
+                            cx.push(Status::Message(message));
                         }
+                        Err(error) => cx.push(Toast(format!("Export failed: {error}"))),
+                    }
+                } else if let Some(confirm) = cx.try_pop::<Confirm>() {
+                    self.confirm.close(cx);
+                    let pending = self.pending.take();
+                    if let (Confirm::Yes, Some(change)) = (confirm, pending) {
+                        self.apply(cx, shared, change);
+                    }
+                } else if cx.try_pop::<SelectionMsg<usize>>().is_some() {
+                    self.sync_selection(cx, shared);
+                } else if let Some(control) = cx.try_pop::<Control>() {
+                    let _span = tracing::debug_span!("crud::control", ?control).entered();
+                    match control {
+                        Control::Create => match self.editor.make_item() {
+                            Ok(item) => self.apply_checked(cx, shared, Pending::Create(item)),
+                            Err(problem) => cx.push(Status::Message(problem)),
+                        },
                         Control::Update => {
                             if let Some(index) = self.selected() {
-                                if let Some(item) = self.editor.make_item() {
-                                    self.entries[index] = item;
-                                    cx.update(self.as_node(&()));
+                                match self.editor.make_item() {
+                                    Ok(item) => {
+                                        self.apply_checked(cx, shared, Pending::Update(index, item))
+                                    }
+                                    Err(problem) => cx.push(Status::Message(problem)),
                                 }
                             }
                         }
                         Control::Delete => {
-                            if let Some(index) = self.selected() {
-                                self.entries.remove(index);
-                                let action = self.list.select(index);
-                                cx.action(&self, action);
-                                self.selected = self.entries.get(index).cloned();
-                                cx.update(self.as_node(&()));
+                            let question = match self.selected() {
+                                Some(index) => format!("Delete {}?", self.entries[index]),
+                                None => format!("Delete {} entries?", self.selection.len()),
+                            };
+                            if !self.selection.is_empty() {
+                                self.ask(cx, question, Pending::Delete);
                             }
                         }
+                        Control::Copy => {
+                            let entries = self.selection.iter().map(|i| &self.entries[*i]);
+                            clipboard::set_tsv(cx, entries.map(|e| [&e.last, &e.first]));
+                            let message = match self.selected.as_ref() {
+                                Some(entry) => format!("Copied {entry}"),
+                                None => format!("Copied {} entries", self.selection.len()),
+                            };
+                            cx.push(Status::Message(message));
+                        }
+                        Control::Sort(key) => self.sort(cx, shared, key),
                     }
                 }
             }
         }
     };
 
-    Window::new(ui, "Create, Read, Update, Delete")
+    StatusBar::new(ui, "Type to filter; select an entry to edit")
+}
+
+/// Menu actions
+pub const MENU: MenuSpec = MenuSpec {
+    shortcuts: &[
+        Shortcut::new("Up, Down", "Select an entry (in the list)"),
+        Shortcut::new("Double-click", "Rename an entry (in the list)"),
+        Shortcut::new(
+            "Right-click",
+            "Edit, duplicate or delete an entry (in the list)",
+        ),
+        Shortcut::new("Alt+C", "Copy the selected entries"),
+        Shortcut::new("Alt+I", "Import entries from the named CSV or vCard file"),
+        Shortcut::new("Alt+E", "Export all entries to the named CSV or vCard file"),
+        Shortcut::new("Ctrl+Shift+D", "Save a debug snapshot"),
+    ],
+    ..MenuSpec::NONE
+};
+
+/// Title of the demo's window
+pub const WINDOW_TITLE: &str = "Create, Read, Update, Delete";
+
+/// Construct the demo in its own window
+pub fn window() -> Window<Shared> {
+    demo_window(ui(), MENU, WINDOW_TITLE)
 }
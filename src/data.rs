@@ -0,0 +1,29 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Application data, shared by all windows
+
+use kas::messages::MessageStack;
+
+use crate::config::Config;
+use crate::counter;
+
+/// Data available to every window
+///
+/// Messages not handled by any widget are passed to this, after which all
+/// windows are updated.
+#[derive(Debug, Default)]
+pub struct Shared {
+    pub config: Config,
+    pub counter: counter::Counter,
+}
+
+impl kas::app::AppData for Shared {
+    fn handle_messages(&mut self, messages: &mut MessageStack) {
+        let _span = tracing::trace_span!("Shared::handle_messages").entered();
+        self.config.handle_messages(messages);
+        self.counter.handle_messages(messages);
+    }
+}
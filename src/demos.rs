@@ -0,0 +1,239 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Registry of demos
+//!
+//! The launcher, command-line and tabbed mode are all driven from [`DEMOS`];
+//! adding a demo requires only a new entry here.
+
+use kas::prelude::*;
+use std::rc::{Rc, Weak};
+
+use crate::data::Shared;
+use crate::menu::{EditAction, FileAction, MenuSpec, MenuTarget, WindowMenu};
+use crate::reload::Reload;
+use crate::shortcuts::is_help_event;
+use crate::toast::Toasts;
+use crate::{cells, counter, crud, flight_booker, temp_conv, timer};
+
+/// Description of a demo
+#[derive(Debug)]
+pub struct Demo {
+    /// Name used on the command-line
+    pub name: &'static str,
+    /// Launcher button label, including access key
+    pub label: &'static str,
+    /// Title for tabbed mode
+    pub title: &'static str,
+    /// Window title
+    pub window_title: &'static str,
+    /// Summary of the 7GUIs task
+    pub description: &'static str,
+    /// Constructor for the demo's UI, if implemented
+    pub make: Option<fn() -> Box<dyn Widget<Data = Shared>>>,
+    /// Demo-specific actions for the window's menu bar
+    pub menu: MenuSpec,
+}
+
+impl Demo {
+    /// True if the demo is implemented
+    pub fn is_available(&self) -> bool {
+        self.make.is_some()
+    }
+
+    /// Construct the demo's UI, for embedding in a tab
+    ///
+    /// This shows [`crate::toast::Toast`] messages from the demo.
+    pub fn widget(&self) -> Option<Box<dyn Widget<Data = Shared>>> {
+        self.make
+            .map(|ui| Box::new(Toasts::new(ui())) as Box<dyn Widget<Data = Shared>>)
+    }
+
+    /// Construct the demo's window, as the application's first window
+    ///
+    /// This reloads the config on change (see [`Reload`]).
+    pub fn window(&self) -> Option<Window<Shared>> {
+        self.widget().map(|inner| {
+            let ui = DemoFrame::new(inner, self.menu, None);
+            Window::new(Reload::new(ui), self.window_title)
+        })
+    }
+
+    /// Construct the demo's window, returning a handle tracking whether it
+    /// is still open
+    ///
+    /// Instances after the first are numbered in the window title.
+    pub fn tracked_window(&self, instance: u32) -> Option<(Window<Shared>, OpenHandle)> {
+        self.widget().map(|inner| {
+            let token = Rc::new(());
+            let handle = OpenHandle(Rc::downgrade(&token));
+            let ui = DemoFrame::new(inner, self.menu, Some(token));
+            let title = numbered_title(self.window_title, instance);
+            (Window::new(ui, title), handle)
+        })
+    }
+
+    /// Find a demo by (command-line) name
+    pub fn find(name: &str) -> Option<&'static Demo> {
+        DEMOS.iter().find(|demo| demo.name == name)
+    }
+}
+
+/// Suffix `title` with the instance number, if not the first: "Timer (2)"
+pub fn numbered_title(title: &str, instance: u32) -> String {
+    match instance {
+        0 | 1 => title.to_string(),
+        n => format!("{title} ({n})"),
+    }
+}
+
+/// Handle to a window created by [`Demo::tracked_window`]
+#[derive(Debug)]
+pub struct OpenHandle(Weak<()>);
+
+impl OpenHandle {
+    /// True until the window is closed
+    pub fn is_open(&self) -> bool {
+        self.0.strong_count() > 0
+    }
+}
+
+impl_scope! {
+    /// Contents of a demo window: a menu bar above the demo
+    ///
+    /// This optionally holds a token for as long as the window exists.
+    #[widget {
+        Data = Shared;
+        layout = column! [self.menu, self.inner];
+    }]
+    struct DemoFrame {
+        core: widget_core!(),
+        #[widget(&())] menu: WindowMenu,
+        #[widget] inner: Box<dyn Widget<Data = Shared>>,
+        /// Receiver of [`FileAction`]s and [`EditAction`]s, if registered
+        target: Option<Id>,
+        _token: Option<Rc<()>>,
+    }
+
+    impl Self {
+        fn new(
+            inner: Box<dyn Widget<Data = Shared>>,
+            menu: MenuSpec,
+            token: Option<Rc<()>>,
+        ) -> Self {
+            DemoFrame {
+                core: Default::default(),
+                menu: WindowMenu::new(menu),
+                inner,
+                target: None,
+                _token: token,
+            }
+        }
+    }
+
+    impl Events for Self {
+        fn handle_event(&mut self, cx: &mut EventCx, _: &Shared, event: Event) -> IsUsed {
+            if is_help_event(&event) {
+                self.menu.show_shortcuts(cx);
+                IsUsed::Used
+            } else {
+                IsUsed::Unused
+            }
+        }
+
+        fn handle_messages(&mut self, cx: &mut EventCx, _: &Shared) {
+            if let Some(MenuTarget(id)) = cx.try_pop() {
+                self.target = Some(id);
+            } else if let Some(action) = cx.try_pop::<FileAction>() {
+                if let Some(id) = self.target.clone() {
+                    cx.send(id, action);
+                }
+            } else if let Some(action) = cx.try_pop::<EditAction>() {
+                if let Some(id) = self.target.clone() {
+                    cx.send(id, action);
+                }
+            }
+        }
+    }
+}
+
+/// Construct a window around a demo's `ui`
+///
+/// Like [`Demo::window`], this adds a menu bar and shows
+/// [`crate::toast::Toast`] messages.
+pub fn demo_window(
+    ui: impl Widget<Data = Shared> + 'static,
+    menu: MenuSpec,
+    title: &str,
+) -> Window<Shared> {
+    Window::new(DemoFrame::new(Box::new(Toasts::new(ui)), menu, None), title)
+}
+
+pub static DEMOS: &[Demo] = &[
+    Demo {
+        name: "counter",
+        label: "&Counter",
+        title: "Counter",
+        description: "A very simple push-button application.",
+        window_title: counter::WINDOW_TITLE,
+        make: Some(|| Box::new(counter::ui())),
+        menu: counter::MENU,
+    },
+    Demo {
+        name: "temp",
+        label: "Tem&perature Converter",
+        title: "Temperature",
+        description: "Bidirectional conversion between Celsius and Fahrenheit.",
+        window_title: temp_conv::WINDOW_TITLE,
+        make: Some(|| Box::new(temp_conv::ui())),
+        menu: temp_conv::MENU,
+    },
+    Demo {
+        name: "flight",
+        label: "Flight &Booker",
+        title: "Flight Booker",
+        description: "Date inputs with real-time validation and constraints.",
+        window_title: flight_booker::WINDOW_TITLE,
+        make: Some(|| Box::new(flight_booker::ui())),
+        menu: flight_booker::MENU,
+    },
+    Demo {
+        name: "timer",
+        label: "&Timer",
+        title: "Timer",
+        description: "Concurrency, competing user/signal interactions and responsiveness.",
+        window_title: timer::WINDOW_TITLE,
+        make: Some(|| Box::new(timer::ui())),
+        menu: timer::MENU,
+    },
+    Demo {
+        name: "crud",
+        label: "CRUD (Create, Read, &Update and &Delete)",
+        title: "CRUD",
+        description:
+            "Separating the domain and presentation logic, managing mutation and filtering.",
+        window_title: crud::WINDOW_TITLE,
+        make: Some(|| Box::new(crud::ui())),
+        menu: crud::MENU,
+    },
+    Demo {
+        name: "circle",
+        label: "Ci&rcle Drawer",
+        title: "Circle Drawer",
+        window_title: "Circle Drawer",
+        description: "Undo/redo, custom drawing and dialog control.",
+        make: None,
+        menu: MenuSpec::NONE,
+    },
+    Demo {
+        name: "cells",
+        label: "Ce&lls",
+        title: "Cells",
+        description: "A spreadsheet: change propagation and widget customization.",
+        window_title: cells::WINDOW_TITLE,
+        make: Some(|| Box::new(cells::ui())),
+        menu: cells::MENU,
+    },
+];
@@ -4,17 +4,62 @@
 //     https://www.apache.org/licenses/LICENSE-2.0
 
 //! Flight booker
+//!
+//! Dates are entered in the chosen [`DateFormat`]. The initial format may be
+//! set in the config:
+//! ```toml
+//! [demos.flight]
+//! date_format = "dotted"
+//! history = "/path/to/flights.json"
+//! # Book at least this many days ahead (default 0)
+//! min_days = 2
+//! # Dates on which no flights may be booked
+//! blackout = ["2026-12-25", "2027-01-01"]
+//! # Time zone of "today", as an offset from UTC (default: the local zone)
+//! utc_offset = "+09:00"
+//! ```
+//!
+//! An optional departure time (HH:MM) may be given for the outbound flight;
+//! it must not have passed when departing today.
+//!
+//! Each booking is appended to a history shown in the window, saved as JSON
+//! to `history` (by default `flights.json` in the platform's data directory).
+//!
+//! A multi-city trip has any number of legs after the outbound flight, each
+//! on or after the date of the leg before it.
+//!
+//! The price shown is an estimate from a simple model: a base fare per flight,
+//! a surcharge for flights at the weekend, a discount on return trips and a
+//! penalty for booking at short notice.
 
-use chrono::{Duration, Local, NaiveDate, ParseError};
+use chrono::{
+    Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, ParseError, Utc,
+    Weekday,
+};
 use kas::prelude::*;
 use kas::widgets::dialog::MessageBox;
-use kas::widgets::{label_any, Adapt, Button, ComboBox, EditBox, EditField, EditGuard, Text};
+use kas::widgets::edit::{EditBox, EditField, EditGuard};
+use kas::widgets::{label_any, AccessLabel, Adapt, Button, Column, ComboBox, Text};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io;
+use std::path::{Path, PathBuf};
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-enum Flight {
+use crate::config::Config;
+use crate::data::Shared;
+use crate::demos::demo_window;
+use crate::menu::MenuSpec;
+use crate::shortcuts::Shortcut;
+use crate::toast::Toast;
+use crate::widgets::{DateEdited, DateField, DateFormat, DateGuard};
+
+/// Type of trip
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Flight {
     #[default]
     OneWay,
     Return,
+    MultiCity,
 }
 
 #[derive(Debug)]
@@ -23,7 +68,17 @@ enum Error {
     OutParse(ParseError),
     RetParse(ParseError),
     OutBeforeToday,
+    TimeParse(ParseError),
+    DeparturePassed,
+    /// The outbound date is within the minimum lead time (in days)
+    LeadTime(u32),
+    /// The date of this field is a blackout date
+    Blackout(Field),
     ReturnTooSoon,
+    /// Index into [`Data::legs`]
+    LegParse(usize, ParseError),
+    /// Index into [`Data::legs`]
+    LegTooSoon(usize),
 }
 impl Error {
     fn is_none(&self) -> bool {
@@ -37,24 +92,433 @@ impl std::fmt::Display for Error {
             Error::OutParse(err) => f.write_fmt(format_args!("Error: outbound date: {err}")),
             Error::RetParse(err) => f.write_fmt(format_args!("Error: return date: {err}")),
             Error::OutBeforeToday => f.write_str("Error: outbound date is before today!"),
+            Error::TimeParse(err) => f.write_fmt(format_args!("Error: departure time: {err}")),
+            Error::DeparturePassed => f.write_str("Error: departure time has passed!"),
+            Error::LeadTime(days) => f.write_fmt(format_args!(
+                "Error: outbound date must be at least {days} days from today!"
+            )),
+            Error::Blackout(field) => f.write_fmt(format_args!(
+                "Error: no flights may be booked on the {} date!",
+                field.name()
+            )),
             Error::ReturnTooSoon => f.write_str("Error: return date must be after outbound date!"),
+            Error::LegParse(index, err) => {
+                f.write_fmt(format_args!("Error: leg {} date: {err}", index + 2))
+            }
+            Error::LegTooSoon(index) => f.write_fmt(format_args!(
+                "Error: leg {} must not be before leg {}!",
+                index + 2,
+                index + 1
+            )),
         }
     }
 }
 
+/// Fare of each flight before adjustments, in euros
+const BASE_FARE: u32 = 120;
+/// Added to the fare of each flight on a Saturday or Sunday
+const WEEKEND_SURCHARGE: u32 = 30;
+/// Percentage taken off the price of a return trip
+const RETURN_DISCOUNT: u32 = 10;
+/// Added when the outbound flight is within [`SHORT_NOTICE_DAYS`] of today
+const SHORT_NOTICE_PENALTY: u32 = 50;
+/// Days from today within which booking is at short notice
+const SHORT_NOTICE_DAYS: i64 = 7;
+
+/// A confirmed booking, as saved in the history
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Booking {
+    flight: Flight,
+    /// The outbound date, followed by the return date or further legs
+    dates: Vec<NaiveDate>,
+    /// Departure time of the outbound flight
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time: Option<NaiveTime>,
+}
+
+impl Booking {
+    /// A one-line description
+    fn summary(&self, format: DateFormat) -> String {
+        let mut dates: Vec<String> = self.dates.iter().map(|date| format.format(*date)).collect();
+        if let Some(out) = dates.first_mut() {
+            *out += &at_time(self.time);
+        }
+        match self.flight {
+            Flight::OneWay => format!("One-way flight on {}", dates.join(", ")),
+            Flight::Return => format!("Return flight: {}", dates.join(" to ")),
+            Flight::MultiCity => format!("Multi-city trip: {}", dates.join(", ")),
+        }
+    }
+
+    /// Estimated price in euros, when booked on `today`
+    fn price(&self, today: NaiveDate) -> u32 {
+        let fare = |date: &NaiveDate| match date.weekday() {
+            Weekday::Sat | Weekday::Sun => BASE_FARE + WEEKEND_SURCHARGE,
+            _ => BASE_FARE,
+        };
+        let mut price: u32 = self.dates.iter().map(fare).sum();
+        if self.flight == Flight::Return {
+            price -= price * RETURN_DISCOUNT / 100;
+        }
+        let short_notice = today + Duration::days(SHORT_NOTICE_DAYS);
+        if self.dates.first().is_some_and(|out| *out < short_notice) {
+            price += SHORT_NOTICE_PENALTY;
+        }
+        price
+    }
+}
+
+/// Format of the departure time
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Parse an optional time in [`TIME_FORMAT`]
+fn parse_time(text: &str) -> Result<Option<NaiveTime>, ParseError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    NaiveTime::parse_from_str(text, TIME_FORMAT).map(Some)
+}
+
+/// " at HH:MM" for `Some` time, else nothing
+fn at_time(time: Option<NaiveTime>) -> String {
+    time.map(|time| format!(" at {}", time.format(TIME_FORMAT)))
+        .unwrap_or_default()
+}
+
+/// Path of the history: option `history` of demo `flight`, else a default
+fn history_path(config: &Config) -> Option<PathBuf> {
+    let path = config.demo_option::<PathBuf>("flight", "history");
+    path.or_else(|| Some(dirs::data_dir()?.join("kas-7guis").join("flights.json")))
+}
+
+/// Load bookings saved by [`save_history`], logging any failure
+///
+/// A file which cannot be parsed is renamed with extension `.corrupt` (when
+/// possible), so that saving does not overwrite it.
+fn load_history(path: &Path) -> Vec<Booking> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return vec![],
+        Err(err) => {
+            log::warn!("failed to read {}: {err}", path.display());
+            return vec![];
+        }
+    };
+    serde_json::from_str(&text).unwrap_or_else(|err| {
+        log::warn!("failed to parse {}: {err}", path.display());
+        let _ = std::fs::rename(path, path.with_extension("json.corrupt"));
+        vec![]
+    })
+}
+
+/// Save `history` to `path` as JSON, creating its directory if needed
+fn save_history(path: &Path, history: &[Booking]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let text = serde_json::to_string_pretty(history)?;
+    std::fs::write(path, text)
+}
+
+/// State of the booking form
 #[derive(Debug)]
-struct Data {
+pub struct Data {
     out: Result<NaiveDate, ParseError>,
     ret: Result<NaiveDate, ParseError>,
+    /// Legs of a multi-city trip after the first (outbound) leg
+    legs: Vec<Result<NaiveDate, ParseError>>,
     flight: Flight,
     error: Error,
+    format: DateFormat,
+    /// True once options are read from the config
+    configured: bool,
+    history: Vec<Booking>,
+    history_path: Option<PathBuf>,
+    /// Minimum days from today to the outbound date
+    min_days: u32,
+    /// Dates which may not be booked
+    blackout: BTreeSet<NaiveDate>,
+    /// Optional departure time of the outbound flight
+    time: Result<Option<NaiveTime>, ParseError>,
+    /// Time zone used for "today"; if `None`, the local zone
+    utc_offset: Option<FixedOffset>,
+    /// Fixed current time, used instead of the clock if set
+    clock: Option<NaiveDateTime>,
 }
+
+/// Shown before the problem with a field
+const WARNING_SIGN: &str = "⚠ ";
+
 impl Data {
+    /// Construct with the outbound date today
+    ///
+    /// If `clock` is given, it is used as the current time (for testing).
+    pub fn new(clock: Option<NaiveDateTime>) -> Self {
+        let mut data = Data {
+            out: Ok(NaiveDate::MIN),
+            ret: Ok(NaiveDate::MIN),
+            legs: vec![],
+            flight: Flight::OneWay,
+            error: Error::None,
+            format: DateFormat::default(),
+            configured: false,
+            history: vec![],
+            history_path: None,
+            min_days: 0,
+            blackout: BTreeSet::new(),
+            time: Ok(None),
+            utc_offset: None,
+            clock,
+        };
+        data.reset_dates();
+        data
+    }
+
+    /// Start from today, with the return a week later
+    fn reset_dates(&mut self) {
+        let today = self.today();
+        self.out = Ok(today);
+        self.ret = Ok(today + Duration::days(7));
+        self.update_error();
+    }
+
+    /// The error message, if the input is invalid
+    pub fn error_text(&self) -> String {
+        self.error.to_string()
+    }
+
+    /// True if the input is valid, allowing booking
+    pub fn can_book(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Set the type of trip
+    ///
+    /// A multi-city trip is given a leg after the outbound flight if it has none.
+    pub fn set_flight(&mut self, flight: Flight) {
+        self.flight = flight;
+        if flight == Flight::MultiCity && self.legs.is_empty() {
+            self.add_leg();
+        }
+        self.update_error();
+    }
+
+    /// Set the date entered in `field`
+    pub fn set_date(&mut self, field: Field, result: Result<NaiveDate, ParseError>) {
+        match field {
+            Field::Out => self.out = result,
+            Field::Return => self.ret = result,
+            Field::Leg(index) => {
+                if let Some(leg) = self.legs.get_mut(index) {
+                    *leg = result;
+                }
+            }
+        }
+        self.update_error();
+    }
+
+    /// Set the departure time entered
+    pub fn set_time(&mut self, result: Result<Option<NaiveTime>, ParseError>) {
+        self.time = result;
+        self.update_error();
+    }
+
+    /// The current time in the configured zone
+    fn now(&self) -> NaiveDateTime {
+        if let Some(now) = self.clock {
+            return now;
+        }
+        match self.utc_offset {
+            Some(offset) => Utc::now().with_timezone(&offset).naive_local(),
+            None => Local::now().naive_local(),
+        }
+    }
+
+    /// Today's date in the configured zone
+    fn today(&self) -> NaiveDate {
+        self.now().date()
+    }
+
+    /// The date entered in `field`, if valid
+    fn date(&self, field: Field) -> Option<NaiveDate> {
+        match field {
+            Field::Out => self.out.ok(),
+            Field::Return => self.ret.ok(),
+            Field::Leg(index) => self.legs.get(index).and_then(|leg| leg.ok()),
+        }
+    }
+
+    /// The fields used by the current flight type
+    fn fields(&self) -> Vec<Field> {
+        let mut fields = vec![Field::Out];
+        match self.flight {
+            Flight::OneWay => (),
+            Flight::Return => fields.push(Field::Return),
+            Flight::MultiCity => fields.extend((0..self.legs.len()).map(Field::Leg)),
+        }
+        fields
+    }
+
+    /// True if `field` is in use and its date is a blackout date
+    fn is_blackout(&self, field: Field) -> bool {
+        let date = self.date(field);
+        self.fields().contains(&field) && date.is_some_and(|date| self.blackout.contains(&date))
+    }
+
+    /// The first field in use with a blackout date
+    fn blackout_field(&self) -> Option<Field> {
+        self.fields()
+            .into_iter()
+            .find(|field| self.is_blackout(*field))
+    }
+
+    /// The problem with the date in `field`, if any
+    pub fn problem(&self, field: Field) -> Option<String> {
+        let problem = match field {
+            Field::Out => self.out_problem(),
+            Field::Return => self.ret_problem(),
+            Field::Leg(index) => self.leg_problem(index),
+        };
+        problem.or_else(|| {
+            let blackout = self.is_blackout(field);
+            blackout.then(|| "no flights on this date".to_string())
+        })
+    }
+
+    /// The earliest outbound date which may be booked
+    fn earliest(&self) -> NaiveDate {
+        self.today() + Duration::days(self.min_days.into())
+    }
+
+    /// The problem with the outbound date, if any
+    fn out_problem(&self) -> Option<String> {
+        match self.out {
+            Err(err) => Some(err.to_string()),
+            Ok(date) if date < self.today() => Some("before today".to_string()),
+            Ok(date) if date < self.earliest() => {
+                Some(format!("less than {} days from today", self.min_days))
+            }
+            Ok(_) => None,
+        }
+    }
+
+    /// True if departing at `time` on the outbound date is in the past
+    fn departure_passed(&self, time: NaiveTime) -> bool {
+        let now = self.now();
+        self.out.is_ok_and(|out| out.and_time(time) < now)
+    }
+
+    /// The problem with the departure time, if any
+    fn time_problem(&self) -> Option<String> {
+        match self.time {
+            Err(err) => Some(err.to_string()),
+            Ok(Some(time)) if self.departure_passed(time) => Some("already passed".to_string()),
+            Ok(_) => None,
+        }
+    }
+
+    /// The error in the departure time, if any
+    fn time_error(&self) -> Option<Error> {
+        match self.time {
+            Err(err) => Some(Error::TimeParse(err)),
+            Ok(Some(time)) if self.departure_passed(time) => Some(Error::DeparturePassed),
+            Ok(_) => None,
+        }
+    }
+
+    /// The problem with the return date, if any
+    fn ret_problem(&self) -> Option<String> {
+        if self.flight != Flight::Return {
+            return None;
+        }
+        match (self.ret, self.out) {
+            (Err(err), _) => Some(err.to_string()),
+            (Ok(ret), Ok(out)) if ret < out => Some("before the outbound date".to_string()),
+            _ => None,
+        }
+    }
+
+    /// The problem with leg `index` (of [`Self::legs`]), if any
+    fn leg_problem(&self, index: usize) -> Option<String> {
+        if self.flight != Flight::MultiCity {
+            return None;
+        }
+        let prev = match index {
+            0 => self.out,
+            index => *self.legs.get(index - 1)?,
+        };
+        match (*self.legs.get(index)?, prev) {
+            (Err(err), _) => Some(err.to_string()),
+            (Ok(date), Ok(prev)) if date < prev => Some("before the previous leg".to_string()),
+            _ => None,
+        }
+    }
+
+    /// The first problem with the legs after `out_date`
+    fn legs_error(&self, out_date: NaiveDate) -> Error {
+        let mut prev = out_date;
+        for (index, leg) in self.legs.iter().enumerate() {
+            match *leg {
+                Err(err) => return Error::LegParse(index, err),
+                Ok(date) if date < prev => return Error::LegTooSoon(index),
+                Ok(date) => prev = date,
+            }
+        }
+        Error::None
+    }
+
+    /// The booking made by valid input
+    fn booking(&self) -> Option<Booking> {
+        if !self.error.is_none() {
+            return None;
+        }
+        let out = self.out.ok()?;
+        let dates = match self.flight {
+            Flight::OneWay => vec![out],
+            Flight::Return => vec![out, self.ret.ok()?],
+            Flight::MultiCity => {
+                let legs = self.legs.iter().map(|leg| leg.ok());
+                std::iter::once(Some(out))
+                    .chain(legs)
+                    .collect::<Option<_>>()?
+            }
+        };
+        Some(Booking {
+            flight: self.flight,
+            dates,
+            time: self.time.ok().flatten(),
+        })
+    }
+
+    /// Save the history after a change, reporting any error as a toast
+    fn save_history(&self, cx: &mut EventCx) {
+        let Some(path) = self.history_path.as_ref() else {
+            return;
+        };
+        if let Err(err) = save_history(path, &self.history) {
+            cx.push(Toast(format!("Failed to save {}: {err}", path.display())));
+        }
+    }
+
+    /// Append a leg one week after the last
+    fn add_leg(&mut self) {
+        let last = self.legs.last().copied().unwrap_or(self.out);
+        let last = last.unwrap_or_else(|_| self.today());
+        self.legs.push(Ok(last + Duration::days(7)));
+    }
+
     fn update_error(&mut self) {
         self.error = match self.out {
             Ok(out_date) => {
-                if out_date < Local::now().naive_local().date() {
+                if out_date < self.today() {
                     Error::OutBeforeToday
+                } else if let Some(error) = self.time_error() {
+                    error
+                } else if out_date < self.earliest() {
+                    Error::LeadTime(self.min_days)
+                } else if let Some(field) = self.blackout_field() {
+                    Error::Blackout(field)
                 } else {
                     match (self.flight, self.ret) {
                         (Flight::OneWay, _) => Error::None,
@@ -66,6 +530,7 @@ impl Data {
                             }
                         }
                         (Flight::Return, Err(err)) => Error::RetParse(err),
+                        (Flight::MultiCity, _) => self.legs_error(out_date),
                     }
                 }
             }
@@ -74,111 +539,341 @@ impl Data {
     }
 }
 
-#[derive(Clone, Debug)]
-struct ActionDate {
-    result: Result<NaiveDate, ParseError>,
-    is_return_field: bool,
+/// Identifies a date field
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Out,
+    Return,
+    /// Index into [`Data::legs`]
+    Leg(usize),
+}
+
+impl Field {
+    /// The field's name, as used in error messages
+    fn name(self) -> String {
+        match self {
+            Field::Out => "outbound".to_string(),
+            Field::Return => "return".to_string(),
+            Field::Leg(index) => format!("leg {}", index + 2),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 struct ActionBook;
 
 #[derive(Clone, Debug)]
-struct Guard {
-    is_return_field: bool,
-}
-impl Guard {
-    fn new(is_return_field: bool) -> Self {
-        Guard { is_return_field }
-    }
+struct AddLeg;
+
+/// Set the return date this many days after the outbound date
+#[derive(Clone, Debug)]
+struct ReturnAfter(i64);
+
+/// A button setting the return date `days` after the outbound date
+fn return_after(label: &str, days: i64) -> impl Widget<Data = Data> {
+    Button::new_msg(label_any(label), ReturnAfter(days))
+        .on_update(|cx, _, data: &Data| cx.set_disabled(data.flight != Flight::Return))
 }
-impl EditGuard for Guard {
+
+/// Emitted by [`TimeGuard`] on each edit
+#[derive(Clone, Debug)]
+struct TimeEdited(Result<Option<NaiveTime>, ParseError>);
+
+/// Guard for the departure time field
+#[derive(Debug, Default)]
+struct TimeGuard;
+
+impl EditGuard for TimeGuard {
     type Data = Data;
 
-    fn edit(edit: &mut EditField<Self>, cx: &mut EventCx, _: &Self::Data) {
-        let result = NaiveDate::parse_from_str(edit.get_str().trim(), "%Y-%m-%d");
+    fn edit(edit: &mut EditField<Self>, cx: &mut EventCx, _: &Data) {
+        let result = parse_time(edit.get_str());
         let act = edit.set_error_state(result.is_err());
         cx.action(edit.id(), act);
+        cx.push(TimeEdited(result));
+    }
 
-        cx.push(ActionDate {
-            result,
-            is_return_field: edit.guard.is_return_field,
-        });
+    fn update(edit: &mut EditField<Self>, cx: &mut ConfigCx, data: &Data) {
+        let act = edit.set_error_state(data.time_problem().is_some());
+        cx.action(edit.id(), act);
     }
+}
 
-    fn update(edit: &mut EditField<Self>, cx: &mut ConfigCx, data: &Self::Data) {
-        if !edit.has_edit_focus() && edit.get_str().is_empty() {
-            if let Ok(date) = match edit.guard.is_return_field {
-                false => data.out,
-                true => data.ret,
-            } {
-                let act = edit.set_string(date.format("%Y-%m-%d").to_string());
-                cx.action(edit.id(), act);
-            }
+#[derive(Clone, Debug)]
+struct RemoveLeg;
+
+/// Delete a booking from the history, by index
+#[derive(Clone, Debug)]
+struct DeleteBooking(usize);
+
+/// A column with one row per leg or booking
+type Rows = Column<Box<dyn Widget<Data = Data>>>;
+
+/// Push (made by `row`) or pop rows until there are `len`
+fn sync_rows(
+    cx: &mut ConfigCx,
+    rows: &mut Rows,
+    data: &Data,
+    len: usize,
+    row: fn(usize) -> Box<dyn Widget<Data = Data>>,
+) {
+    while rows.len() > len {
+        rows.pop(cx);
+    }
+    while rows.len() < len {
+        let widget = row(rows.len());
+        rows.push(cx, data, widget);
+    }
+}
+
+/// Text of a field's problem, shown under the field
+fn problem_text(problem: Option<String>) -> String {
+    problem
+        .map(|p| format!("{WARNING_SIGN}{p}"))
+        .unwrap_or_default()
+}
+
+/// A date field for a leg, with its problem shown below
+fn leg_row(index: usize) -> Box<dyn Widget<Data = Data>> {
+    let field = Field::Leg(index);
+    Box::new(kas::column![
+        Text::new(move |_, _: &Data| format!("Leg {}:", index + 2)),
+        DateField::new(
+            DateGuard::new(field, |data: &Data, field| data.date(*field))
+                .with_disabled(|data, _| data.flight != Flight::MultiCity)
+                .with_format(|data| data.format)
+                .with_invalid(|data, field| data.problem(*field).is_some())
+        ),
+        Text::new(move |_, data: &Data| problem_text(data.problem(field))),
+    ])
+}
+
+impl_scope! {
+    /// Fields for the legs of a multi-city trip after the first
+    #[impl_default]
+    #[widget {
+        layout = column! [
+            self.fields,
+            row! [self.add, self.remove],
+        ];
+    }]
+    struct Legs {
+        core: widget_core!(),
+        #[widget] fields: Rows = Column::new(vec![]),
+        #[widget(&())] add: Button<AccessLabel> = Button::label_msg("&Add leg", AddLeg),
+        #[widget(&())] remove: Button<AccessLabel> = Button::label_msg("&Remove leg", RemoveLeg),
+    }
+    impl Events for Self {
+        type Data = Data;
+
+        fn update(&mut self, cx: &mut ConfigCx, data: &Data) {
+            sync_rows(cx, &mut self.fields, data, data.legs.len(), leg_row);
+
+            let multi_city = data.flight == Flight::MultiCity;
+            cx.set_disabled(self.add.id(), !multi_city);
+            cx.set_disabled(self.remove.id(), !multi_city || data.legs.is_empty());
         }
-        if edit.guard.is_return_field {
-            cx.set_disabled(edit.id(), data.flight == Flight::OneWay);
+    }
+}
+
+/// A row of the history, with a button to delete the booking
+fn history_row(index: usize) -> Box<dyn Widget<Data = Data>> {
+    Box::new(kas::row![
+        Text::new(move |_, data: &Data| {
+            let booking = data.history.get(index);
+            booking.map(|b| b.summary(data.format)).unwrap_or_default()
+        }),
+        Button::label_msg("Delete", DeleteBooking(index)).map_any(),
+    ])
+}
+
+impl_scope! {
+    /// Past bookings
+    #[impl_default]
+    #[widget {
+        layout = column! [
+            self.heading,
+            self.rows,
+        ];
+    }]
+    struct History {
+        core: widget_core!(),
+        #[widget] heading: Text<Data, String> = Text::new(|_, data: &Data| {
+            let empty = data.history.is_empty();
+            let heading = if empty { "No past bookings" } else { "Past bookings:" };
+            heading.to_string()
+        }),
+        #[widget] rows: Rows = Column::new(vec![]),
+    }
+    impl Events for Self {
+        type Data = Data;
+
+        fn update(&mut self, cx: &mut ConfigCx, data: &Data) {
+            sync_rows(cx, &mut self.rows, data, data.history.len(), history_row);
         }
     }
 }
 
-pub fn window() -> Window<()> {
-    let out_date = Local::now().naive_local().date();
-    let data = Data {
-        out: Ok(out_date),
-        ret: Ok(out_date + Duration::days(7)),
-        flight: Flight::OneWay,
-        error: Error::None,
-    };
+pub fn ui() -> impl Widget<Data = Shared> {
+    let data = Data::new(None);
+    let formats = DateFormat::ALL.map(|format| (format.label(), format));
 
     let ui = kas::column![
         ComboBox::new(
             [
                 ("One-way flight", Flight::OneWay),
-                ("Return flight", Flight::Return)
+                ("Return flight", Flight::Return),
+                ("Multi-city trip", Flight::MultiCity)
             ],
             |_, data: &Data| data.flight
         ),
-        EditBox::new(Guard::new(false)),
-        EditBox::new(Guard::new(true)),
+        ComboBox::new(formats, |_, data: &Data| data.format),
+        DateField::new(
+            DateGuard::new(Field::Out, |data: &Data, _| data.out.ok())
+                .with_format(|data| data.format)
+                .with_invalid(|data, field| data.problem(*field).is_some())
+        ),
+        Text::new(|_, data: &Data| problem_text(data.problem(Field::Out))),
+        kas::row![label_any("Departure time:"), EditBox::new(TimeGuard)],
+        Text::new(|_, data: &Data| problem_text(data.time_problem())),
+        kas::row![
+            DateField::new(
+                DateGuard::new(Field::Return, |data: &Data, _| data.ret.ok())
+                    .with_disabled(|data, _| data.flight != Flight::Return)
+                    .with_format(|data| data.format)
+                    .with_invalid(|data, field| data.problem(*field).is_some())
+            ),
+            return_after("+3 days", 3),
+            return_after("+1 week", 7),
+            return_after("+2 weeks", 14),
+        ],
+        Text::new(|_, data: &Data| problem_text(data.problem(Field::Return))),
+        Legs::default(),
         Text::new(|_, data: &Data| format!("{}", data.error)),
-        Button::new_msg(label_any("Book"), ActionBook)
-            .on_update(|cx, _, data: &Data| cx.set_disabled(!data.error.is_none())),
+        kas::row![
+            Button::new_msg(label_any("Book"), ActionBook)
+                .on_update(|cx, _, data: &Data| cx.set_disabled(!data.error.is_none())),
+            Text::new(|_, data: &Data| {
+                let today = data.today();
+                let price = data.booking().map(|booking| booking.price(today));
+                price
+                    .map(|price| format!("Estimated price: €{price}"))
+                    .unwrap_or_default()
+            }),
+        ],
+        History::default(),
     ];
 
     let ui = Adapt::new(ui, data)
-        .on_message(|_, data, flight| {
-            data.flight = flight;
+        .on_update(|_, data, shared: &Shared| {
+            if !data.configured {
+                data.configured = true;
+                if let Some(format) = shared.config.demo_option("flight", "date_format") {
+                    data.format = format;
+                }
+                data.history_path = history_path(&shared.config);
+                if let Some(path) = data.history_path.as_ref() {
+                    data.history = load_history(path);
+                }
+                let config = &shared.config;
+                data.min_days = config.demo_option("flight", "min_days").unwrap_or(0);
+                data.blackout = config.demo_option("flight", "blackout").unwrap_or_default();
+                if let Some(offset) = config.demo_option::<String>("flight", "utc_offset") {
+                    match offset.parse() {
+                        Ok(offset) => data.utc_offset = Some(offset),
+                        Err(err) => log::warn!("invalid utc_offset {offset:?}: {err}"),
+                    }
+                }
+                // Nothing is entered yet, so start from today in the configured zone
+                data.reset_dates();
+            }
+        })
+        .on_message(|_, data, format: DateFormat| data.format = format)
+        .on_message(|_, data, flight| data.set_flight(flight))
+        .on_message(|_, data, AddLeg| {
+            data.add_leg();
             data.update_error();
         })
-        .on_message(|_, data, parse: ActionDate| {
-            if parse.is_return_field {
-                data.ret = parse.result;
-            } else {
-                data.out = parse.result;
-            }
-
+        .on_message(|_, data, RemoveLeg| {
+            data.legs.pop();
             data.update_error();
         })
+        .on_message(|_, data, edited: DateEdited<Field>| data.set_date(edited.tag, edited.result))
+        .on_message(|_, data, ReturnAfter(days)| {
+            if let Ok(out) = data.out {
+                data.ret = Ok(out + Duration::days(days));
+                data.update_error();
+            }
+        })
+        .on_message(|_, data, TimeEdited(result)| data.set_time(result))
+        .on_message(|cx, data, DeleteBooking(index)| {
+            if index < data.history.len() {
+                data.history.remove(index);
+                data.save_history(cx);
+            }
+        })
         .on_message(|cx, data, ActionBook| {
-            let msg = if !data.error.is_none() {
+            let mut msg = if !data.error.is_none() {
                 // should be impossible since the button is disabled
                 format!("{}", data.error)
             } else {
+                let time = at_time(data.time.ok().flatten());
                 match data.flight {
                     Flight::OneWay => format!(
-                        "You have booked a one-way flight on {}",
-                        data.out.unwrap().format("%Y-%m-%d")
+                        "You have booked a one-way flight on {}{time}",
+                        data.format.format(data.out.unwrap())
                     ),
                     Flight::Return => format!(
-                        "You have booked an out-bound flight on {} and a return flight on {}",
-                        data.out.unwrap().format("%Y-%m-%d"),
-                        data.ret.unwrap().format("%Y-%m-%d"),
+                        "You have booked an out-bound flight on {}{time} and a return flight on {}",
+                        data.format.format(data.out.unwrap()),
+                        data.format.format(data.ret.unwrap()),
                     ),
+                    Flight::MultiCity => {
+                        let dates = std::iter::once(data.out).chain(data.legs.iter().copied());
+                        let mut legs: Vec<String> = dates
+                            .enumerate()
+                            .map(|(i, date)| {
+                                format!("Leg {}: {}", i + 1, data.format.format(date.unwrap()))
+                            })
+                            .collect();
+                        legs[0] += &time;
+                        format!("You have booked a multi-city trip:\n{}", legs.join("\n"))
+                    }
                 }
             };
-            cx.add_window::<()>(MessageBox::new(msg).into_window("Booker result"));
+            if let Some(booking) = data.booking() {
+                let price = booking.price(data.today());
+                msg += &format!("\nPrice: €{price}");
+                data.history.push(booking);
+                data.save_history(cx);
+            }
+            cx.add_window::<Shared>(MessageBox::new(msg).into_window("Booker result"));
         });
 
-    Window::new(ui, "Flight Booker")
+    ui
+}
+
+/// Menu actions
+pub const MENU: MenuSpec = MenuSpec {
+    shortcuts: &[
+        Shortcut::new("Up, Down", "Next or previous day (in a date field)"),
+        Shortcut::new(
+            "Shift+Up, Shift+Down",
+            "Next or previous week (in a date field)",
+        ),
+        Shortcut::new(
+            "Ctrl+Up, Ctrl+Down",
+            "Next or previous month (in a date field)",
+        ),
+        Shortcut::new("Alt+A, Alt+R", "Add or remove a leg (multi-city trips)"),
+    ],
+    ..MenuSpec::NONE
+};
+
+/// Title of the demo's window
+pub const WINDOW_TITLE: &str = "Flight Booker";
+
+/// Construct the demo in its own window
+pub fn window() -> Window<Shared> {
+    demo_window(ui(), MENU, WINDOW_TITLE)
 }
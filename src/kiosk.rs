@@ -0,0 +1,80 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Kiosk mode: cycle through all demos, forever
+//!
+//! kas (0.14) cannot make a window full-screen, so the kiosk is a normal
+//! window; maximise it using the window manager.
+
+use kas::prelude::*;
+use kas::widgets::{Separator, Stack, Text};
+use std::time::Duration;
+
+use crate::data::Shared;
+use crate::demos::DEMOS;
+use crate::reload::Reload;
+
+/// Timer used to advance to the next demo
+const NEXT_TIMER: u64 = 0;
+
+/// Fallback for an invalid `kiosk_interval`
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+
+pub fn window() -> Window<Shared> {
+    let ui = impl_anon! {
+        #[widget {
+            layout = column! [
+                self.title,
+                Separator::new(),
+                self.stack,
+            ];
+        }]
+        struct {
+            core: widget_core!(),
+            #[widget(&self.index)] title: Text<usize, String> = Text::new(|_, index: &usize| {
+                let demo = DEMOS.iter().filter(|demo| demo.is_available()).nth(*index);
+                demo.map(|demo| demo.window_title.to_string()).unwrap_or_default()
+            }),
+            #[widget] stack: Stack<Box<dyn Widget<Data = Shared>>> = Stack::new(),
+            index: usize,
+        }
+        impl Events for Self {
+            type Data = Shared;
+
+            fn configure(&mut self, cx: &mut ConfigCx) {
+                // The demos are constructed by the first timer, which has data
+                cx.request_timer(self.id(), NEXT_TIMER, Duration::ZERO);
+            }
+
+            fn handle_event(&mut self, cx: &mut EventCx, data: &Shared, event: Event) -> IsUsed {
+                match event {
+                    Event::Timer(NEXT_TIMER) => {
+                        let mut cx = cx.config_cx();
+                        if self.stack.is_empty() {
+                            for widget in DEMOS.iter().filter_map(|demo| demo.widget()) {
+                                self.stack.push(&mut cx, data, widget);
+                            }
+                            self.index = 0;
+                        } else {
+                            self.index = (self.index + 1) % self.stack.len();
+                        }
+                        self.stack.set_active(&mut cx, data, self.index);
+                        cx.update(self.as_node(data));
+
+                        let secs = data.config.kiosk_interval;
+                        let interval = Duration::try_from_secs_f64(secs)
+                            .ok()
+                            .filter(|d| !d.is_zero())
+                            .unwrap_or(DEFAULT_INTERVAL);
+                        cx.request_timer(self.id(), NEXT_TIMER, interval);
+                        IsUsed::Used
+                    }
+                    _ => IsUsed::Unused,
+                }
+            }
+        }
+    };
+    Window::new(Reload::new(ui), "7GUIs Kiosk")
+}
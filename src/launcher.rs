@@ -0,0 +1,379 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Launcher window
+
+use kas::event::Command;
+use kas::prelude::*;
+use kas::view::filter::{
+    ContainsCaseInsensitive, Filter, FilterList, KeystrokeGuard, SetFilter, UnsafeFilteredList,
+};
+use kas::view::{Driver, ListView};
+use kas::widgets::menu::MenuBar;
+use kas::widgets::{Button, CheckButton, Column, EditBox, Label, Popup, Tab, TabStack, Text};
+use kas::WindowId;
+use std::time::Duration;
+
+use crate::config::ConfigMsg;
+use crate::data::Shared;
+use crate::demos::{numbered_title, Demo, OpenHandle, DEMOS};
+use crate::palette::{self, Palette};
+use crate::reload::Reload;
+use crate::shortcuts::{is_help_event, Shortcut, ShortcutSheet};
+use crate::theme::{self, ColorMode, SetTheme, THEMES};
+use crate::{about, logs};
+
+/// Open a demo
+#[derive(Clone, Debug)]
+struct OpenDemo(&'static Demo);
+
+#[derive(Clone, Debug)]
+struct SetTabbed(bool);
+
+#[derive(Clone, Debug)]
+struct ShowAbout;
+
+#[derive(Clone, Debug)]
+struct ShowShortcuts;
+
+#[derive(Clone, Debug)]
+struct Quit;
+
+#[derive(Clone, Debug)]
+struct ShowLogs;
+
+#[derive(Clone, Debug)]
+struct CloseWindow(WindowId);
+
+/// Key bindings of the launcher window
+const SHORTCUTS: &[Shortcut] = &[
+    Shortcut::new("Ctrl+P", "Open the command palette"),
+    Shortcut::new("Alt+O", "Toggle opening demos in tabs"),
+    Shortcut::new("Alt+S", "Toggle reopening demos at start-up"),
+];
+
+/// Timer used to poll for windows closed by the user
+///
+/// kas 0.14 has no window-closed notification: closing a window just drops
+/// it, informing neither other windows' widgets nor the app's data. Windows
+/// closed from the launcher are removed from its list immediately; others
+/// are detected by polling their [`OpenHandle`]. The timer only runs while
+/// windows are open.
+const POLL_TIMER: u64 = 0;
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Timer used to restore demos once the launcher is open
+const RESTORE_TIMER: u64 = 1;
+
+/// A demo opened in its own window
+struct OpenWindow {
+    demo: &'static Demo,
+    id: WindowId,
+    /// Instance number, counting from 1 amongst open windows of `demo`
+    instance: u32,
+    handle: OpenHandle,
+}
+
+/// Entry in the list of open windows
+fn open_entry(window: &OpenWindow) -> Box<dyn Widget<Data = ()>> {
+    Box::new(kas::row![
+        Label::new(numbered_title(window.demo.title, window.instance)),
+        Button::label_msg("Close", CloseWindow(window.id)),
+    ])
+}
+
+impl Filter<&'static Demo> for ContainsCaseInsensitive {
+    fn matches(&self, demo: &&'static Demo) -> bool {
+        Filter::<&str>::matches(self, &demo.title)
+            || Filter::<&str>::matches(self, &demo.name)
+            || Filter::<&str>::matches(self, &demo.description)
+    }
+}
+
+type DemoList = Vec<&'static Demo>;
+type FilteredList = UnsafeFilteredList<DemoList>;
+
+/// Makes a button and description for each demo; keys index [`DEMOS`]
+struct ButtonDriver;
+impl Driver<&'static Demo, FilteredList> for ButtonDriver {
+    type Widget = Box<dyn Widget<Data = &'static Demo>>;
+    fn make(&mut self, key: &usize) -> Self::Widget {
+        let demo = &DEMOS[*key];
+        Box::new(kas::row![
+            Button::label_msg(demo.label, OpenDemo(demo))
+                .map_any()
+                .on_update(|cx, _, demo: &&'static Demo| cx.set_disabled(!demo.is_available())),
+            Label::new(demo.description),
+        ])
+    }
+    fn set_key(&mut self, widget: &mut Self::Widget, key: &usize) {
+        // The label (with its access key) and message are fixed when made, so
+        // a widget reused for another demo is rebuilt
+        *widget = self.make(key);
+    }
+}
+
+fn menu_bar() -> MenuBar<Shared> {
+    MenuBar::builder()
+        .menu("&File", |menu| {
+            menu.entry("&Quit", Quit);
+        })
+        .menu("&View", |menu| {
+            theme::add_submenus(menu);
+            menu.entry("&Logs", ShowLogs);
+        })
+        .menu("&Help", |menu| {
+            menu.entry("&Keyboard shortcuts", ShowShortcuts);
+            menu.entry("&About", ShowAbout);
+        })
+        .build()
+}
+
+/// Entries for the command palette
+fn palette_entries() -> Vec<palette::Entry> {
+    use palette::Entry;
+    let mut entries: Vec<Entry> = DEMOS
+        .iter()
+        .filter(|demo| demo.is_available())
+        .map(|demo| Entry::msg(format!("Open {}", demo.title), OpenDemo(demo)))
+        .collect();
+    for (label, name) in THEMES {
+        entries.push(Entry::msg(
+            format!("Theme: {}", label.replace('&', "")),
+            SetTheme(name),
+        ));
+    }
+    for (label, mode) in ColorMode::ENTRIES {
+        entries.push(Entry::msg(format!("Colours: {label}"), mode));
+    }
+    entries.push(Entry::msg("Show logs", ShowLogs));
+    entries.push(Entry::msg("Keyboard shortcuts", ShowShortcuts));
+    entries.push(Entry::msg("About", ShowAbout));
+    entries.push(Entry::msg("Quit", Quit));
+    entries
+}
+
+pub fn window(tabbed: bool) -> Window<Shared> {
+    type ButtonView = ListView<FilteredList, ButtonDriver, kas::dir::Down>;
+    type ButtonList = FilterList<DemoList, ContainsCaseInsensitive, ButtonView>;
+    let buttons = FilterList::new(
+        ButtonView::new(ButtonDriver),
+        ContainsCaseInsensitive::new(),
+    );
+
+    let ui = impl_anon! {
+        #[widget {
+            layout = column! [
+                self.menu,
+                row! [
+                    column! [
+                        row! ["Filter:", self.filter],
+                        self.buttons,
+                        self.tabbed_check,
+                        self.restore_check,
+                        self.status_text,
+                        "Open windows:",
+                        self.open_list,
+                    ],
+                    self.tabs,
+                ],
+            ];
+        }]
+        struct {
+            core: widget_core!(),
+            #[widget] menu: MenuBar<Shared> = menu_bar(),
+            #[widget(&())] filter: EditBox<KeystrokeGuard> = EditBox::new(KeystrokeGuard),
+            #[widget(&self.demos)] buttons: ButtonList = buttons,
+            demos: DemoList = DEMOS.iter().collect(),
+            #[widget(&self.tabbed)] tabbed_check: CheckButton<bool> =
+                CheckButton::new_msg("&Open in tabs", |_, tabbed: &bool| *tabbed, SetTabbed),
+            #[widget] restore_check: CheckButton<Shared> = CheckButton::new_msg(
+                "Reopen demos at &start-up",
+                |_, data: &Shared| data.config.restore_demos,
+                ConfigMsg::RestoreDemos,
+            ),
+            #[widget(&self.status)] status_text: Text<String, String> =
+                Text::new(|_, status: &String| status.clone()),
+            #[widget(&())] open_list: Column<Box<dyn Widget<Data = ()>>> = Column::new(vec![]),
+            /// Opened with Ctrl+P
+            #[widget(&())] palette: Popup<Palette> =
+                Popup::new(Palette::new(palette_entries()), Direction::Down),
+            /// Opened with F1
+            #[widget(&())] sheet: Popup<ShortcutSheet> =
+                Popup::new(ShortcutSheet::new(SHORTCUTS), Direction::Down),
+            #[widget] tabs: TabStack<Box<dyn Widget<Data = Shared>>> = TabStack::new(),
+            tabbed: bool = tabbed,
+            /// Demos opened in windows, matching `open_list`
+            open: Vec<OpenWindow>,
+            /// Demos opened in `tabs`, by index
+            tab_demos: Vec<&'static Demo>,
+            status: String,
+            /// True once restoring demos is scheduled, which happens only once
+            restored: bool,
+        }
+        impl Self {
+            /// Open `demo`, reusing an existing instance unless `force`
+            fn open(&mut self, cx: &mut EventCx, data: &Shared, demo: &'static Demo, force: bool) {
+                self.status.clear();
+                if self.tabbed {
+                    let existing = self.tab_demos.iter().position(|d| std::ptr::eq(*d, demo));
+                    let mut cx = cx.config_cx();
+                    if let Some(index) = existing.filter(|_| !force) {
+                        self.tabs.set_active(&mut cx, data, index);
+                    } else if let Some(widget) = demo.widget() {
+                        let count = self.tab_demos.iter().filter(|d| std::ptr::eq(**d, demo));
+                        let instance = count.count() as u32 + 1;
+                        let tab = Tab::new(numbered_title(demo.title, instance));
+                        let index = self.tabs.push(&mut cx, data, tab, widget);
+                        self.tab_demos.push(demo);
+                        self.tabs.set_active(&mut cx, data, index);
+                    }
+                } else {
+                    self.prune(cx);
+                    if !force && self.open.iter().any(|w| std::ptr::eq(w.demo, demo)) {
+                        // kas cannot raise an existing window, so we explain instead
+                        self.status = format!(
+                            "{} is already open (Shift+click for another)",
+                            demo.title
+                        );
+                    } else {
+                        self.open_window(cx, demo);
+                    }
+                }
+                self.record(cx, data);
+                cx.update(self.as_node(data));
+            }
+
+            /// Open a new window for `demo`
+            fn open_window(&mut self, cx: &mut EventCx, demo: &'static Demo) {
+                let instance = self.free_instance(demo);
+                let Some((window, handle)) = demo.tracked_window(instance) else {
+                    return;
+                };
+                let id = cx.add_window::<Shared>(window);
+                let window = OpenWindow {
+                    demo,
+                    id,
+                    instance,
+                    handle,
+                };
+                self.open_list.push(&mut cx.config_cx(), &(), open_entry(&window));
+                self.open.push(window);
+                if self.open.len() == 1 {
+                    cx.request_timer(self.id(), POLL_TIMER, POLL_INTERVAL);
+                }
+            }
+
+            /// Lowest instance number not used by an open window of `demo`
+            fn free_instance(&self, demo: &'static Demo) -> u32 {
+                let used = |n| {
+                    let mut iter = self.open.iter();
+                    iter.any(|w| std::ptr::eq(w.demo, demo) && w.instance == n)
+                };
+                (1..).find(|n| !used(*n)).unwrap()
+            }
+
+            /// Remove entries for windows which have been closed
+            fn prune(&mut self, cx: &mut EventCx) {
+                for index in (0..self.open.len()).rev() {
+                    if !self.open[index].handle.is_open() {
+                        self.open.remove(index);
+                        self.open_list.remove(cx, index);
+                    }
+                }
+            }
+
+            /// Save the names of open demos (if changed) for restoring later
+            fn record(&self, cx: &mut EventCx, data: &Shared) {
+                let names: Vec<String> = self
+                    .tab_demos
+                    .iter()
+                    .chain(self.open.iter().map(|w| &w.demo))
+                    .map(|demo| demo.name.to_string())
+                    .collect();
+                if names != data.config.open_demos {
+                    cx.push(ConfigMsg::OpenDemos(names));
+                }
+            }
+        }
+        impl Events for Self {
+            type Data = Shared;
+
+            fn configure(&mut self, cx: &mut ConfigCx) {
+                // Windows may only be added from an event context. The
+                // launcher may be re-configured, but must not restore again.
+                if !self.restored {
+                    self.restored = true;
+                    cx.request_timer(self.id(), RESTORE_TIMER, Duration::ZERO);
+                }
+            }
+
+            fn handle_event(&mut self, cx: &mut EventCx, data: &Shared, event: Event) -> IsUsed {
+                match event {
+                    Event::Timer(POLL_TIMER) => {
+                        self.prune(cx);
+                        self.record(cx, data);
+                        if !self.open.is_empty() {
+                            cx.request_timer(self.id(), POLL_TIMER, POLL_INTERVAL);
+                        }
+                        IsUsed::Used
+                    }
+                    // kas maps Ctrl+P to Print
+                    Event::Command(Command::Print, _) => {
+                        self.palette.reset(cx);
+                        self.palette.open(cx, &(), self.filter.id(), true);
+                        IsUsed::Used
+                    }
+                    event if is_help_event(&event) => {
+                        self.sheet.open(cx, &(), self.menu.id(), true);
+                        IsUsed::Used
+                    }
+                    Event::Timer(RESTORE_TIMER) => {
+                        if data.config.restore_demos {
+                            let demos = data.config.open_demos.iter();
+                            for demo in demos.filter_map(|name| Demo::find(name)) {
+                                self.open(cx, data, demo, true);
+                            }
+                        }
+                        IsUsed::Used
+                    }
+                    _ => IsUsed::Unused,
+                }
+            }
+
+            fn handle_messages(&mut self, cx: &mut EventCx, data: &Shared) {
+                // The palette's own message is below this, and handled next
+                if let Some(palette::Done) = cx.try_pop() {
+                    self.palette.close(cx);
+                }
+                theme::handle_messages(cx);
+
+                if let Some(SetFilter(value)) = cx.try_pop() {
+                    self.buttons.set_filter(&mut cx.config_cx(), &self.demos, value);
+                } else if let Some(OpenDemo(demo)) = cx.try_pop() {
+                    let force = cx.modifiers().shift_key();
+                    self.open(cx, data, demo, force);
+                } else if let Some(SetTabbed(tabbed)) = cx.try_pop() {
+                    self.tabbed = tabbed;
+                } else if let Some(Quit) = cx.try_pop() {
+                    cx.exit();
+                } else if let Some(ShowLogs) = cx.try_pop() {
+                    cx.add_window::<Shared>(logs::window());
+                } else if let Some(ShowShortcuts) = cx.try_pop() {
+                    self.sheet.open(cx, &(), self.menu.id(), true);
+                } else if let Some(ShowAbout) = cx.try_pop() {
+                    cx.add_window::<Shared>(about::window());
+                } else if let Some(CloseWindow(id)) = cx.try_pop() {
+                    cx.close_window(id);
+                    if let Some(index) = self.open.iter().position(|w| w.id == id) {
+                        self.open.remove(index);
+                        self.open_list.remove(cx, index);
+                        self.record(cx, data);
+                    }
+                }
+            }
+        }
+    };
+    Window::new(Reload::new(ui), "7GUIs Launcher")
+}
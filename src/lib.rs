@@ -0,0 +1,46 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! 7-GUIs demos, for embedding in other kas applications
+//!
+//! Each demo module has a `ui()` constructor for embedding and a `window()`
+//! constructor; all expect [`data::Shared`] as the application data. The
+//! [`demos::DEMOS`] registry lists them all.
+//!
+//! ```no_run
+//! use kas_7guis::data::Shared;
+//!
+//! fn main() -> Result<(), kas::app::Error> {
+//!     kas::app::Default::new(Shared::default())?
+//!         .with(kas_7guis::counter::window())
+//!         .run()
+//! }
+//! ```
+
+mod about;
+pub mod animation;
+pub mod cells;
+pub mod clipboard;
+pub mod config;
+pub mod counter;
+pub mod crud;
+pub mod data;
+pub mod demos;
+pub mod flight_booker;
+pub mod kiosk;
+pub mod launcher;
+pub mod logs;
+pub mod menu;
+mod palette;
+pub mod reload;
+pub mod shortcuts;
+pub mod snapshot;
+pub mod status;
+pub mod temp_conv;
+pub mod theme;
+pub mod timer;
+pub mod toast;
+mod undo;
+pub mod widgets;
@@ -0,0 +1,159 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Log capture and viewer
+
+use kas::prelude::*;
+use kas::view::filter::{
+    ContainsCaseInsensitive, Filter, FilterList, KeystrokeGuard, SetFilter, UnsafeFilteredList,
+};
+use kas::view::{Driver, ListView};
+use kas::widgets::{Button, EditBox, ScrollBars, Text};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::data::Shared;
+
+/// Number of log lines kept
+const CAPACITY: usize = 1000;
+
+static BUFFER: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Forwards to `env_logger`, also recording to [`BUFFER`]
+struct Logger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.inner.matches(record) {
+            let line = format!("[{} {}] {}", record.level(), record.target(), record.args());
+            let mut buffer = BUFFER.lock().unwrap();
+            if buffer.len() == CAPACITY {
+                buffer.pop_front();
+            }
+            buffer.push_back(line);
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Install the logger (configured as for `env_logger`, via `RUST_LOG`)
+pub fn init() {
+    let inner = env_logger::Builder::from_default_env().build();
+    let max_level = inner.filter();
+    if log::set_boxed_logger(Box::new(Logger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Install a detailed `tracing` subscriber, printing all spans with timings
+///
+/// This is independent of [`init`]: `log` records are not forwarded.
+pub fn init_tracing() {
+    use tracing_subscriber::fmt::format::FmtSpan;
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_span_events(FmtSpan::CLOSE)
+        .finish();
+    if let Err(err) = tracing::subscriber::set_global_default(subscriber) {
+        log::warn!("failed to install tracing subscriber: {err}");
+    }
+}
+
+#[derive(Clone, Debug)]
+struct LogLine(String);
+
+impl Filter<LogLine> for ContainsCaseInsensitive {
+    fn matches(&self, item: &LogLine) -> bool {
+        Filter::<&str>::matches(self, &item.0.as_str())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Refresh;
+
+const REFRESH_TIMER: u64 = 0;
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub fn window() -> Window<Shared> {
+    struct LineDriver;
+    type FilteredList = UnsafeFilteredList<Vec<LogLine>>;
+    impl Driver<LogLine, FilteredList> for LineDriver {
+        type Widget = Text<LogLine, String>;
+        fn make(&mut self, _: &usize) -> Self::Widget {
+            Text::new(|_, line: &LogLine| line.0.clone())
+        }
+    }
+    type LineView = ListView<FilteredList, LineDriver, kas::dir::Down>;
+    type LineList = FilterList<Vec<LogLine>, ContainsCaseInsensitive, LineView>;
+    let list = FilterList::new(LineView::new(LineDriver), ContainsCaseInsensitive::new());
+
+    let ui = impl_anon! {
+        #[widget {
+            layout = column! [
+                row! [
+                    "Filter:",
+                    self.filter,
+                    Button::label_msg("&Refresh", Refresh),
+                ],
+                self.list,
+            ];
+        }]
+        struct {
+            core: widget_core!(),
+            #[widget(&())] filter: EditBox<KeystrokeGuard> = EditBox::new(KeystrokeGuard),
+            #[widget(&self.lines)] list: ScrollBars<LineList> = ScrollBars::new(list),
+            lines: Vec<LogLine>,
+            filter_value: String,
+        }
+        impl Self {
+            fn refresh(&mut self, cx: &mut EventCx) {
+                let buffer = BUFFER.lock().unwrap();
+                self.lines = buffer.iter().cloned().map(LogLine).collect();
+                drop(buffer);
+                let filter = self.filter_value.clone();
+                self.list.set_filter(&mut cx.config_cx(), &self.lines, filter);
+            }
+        }
+        impl Events for Self {
+            type Data = ();
+
+            fn configure(&mut self, cx: &mut ConfigCx) {
+                cx.request_timer(self.id(), REFRESH_TIMER, Duration::ZERO);
+            }
+
+            fn handle_event(&mut self, cx: &mut EventCx, _: &(), event: Event) -> IsUsed {
+                match event {
+                    Event::Timer(REFRESH_TIMER) => {
+                        self.refresh(cx);
+                        cx.request_timer(self.id(), REFRESH_TIMER, REFRESH_INTERVAL);
+                        IsUsed::Used
+                    }
+                    _ => IsUsed::Unused,
+                }
+            }
+
+            fn handle_messages(&mut self, cx: &mut EventCx, _: &()) {
+                if let Some(SetFilter(value)) = cx.try_pop() {
+                    self.filter_value = value.clone();
+                    self.list.set_filter(&mut cx.config_cx(), &self.lines, value);
+                } else if let Some(Refresh) = cx.try_pop() {
+                    self.refresh(cx);
+                }
+            }
+        }
+    };
+    Window::new(ui.map_any(), "Logs")
+}
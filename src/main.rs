@@ -5,69 +5,145 @@
 
 //! 7-GUIs launcher
 
-mod cells;
-mod counter;
-mod crud;
-mod flight_booker;
-mod temp_conv;
-mod timer;
+use kas::theme::ThemeControl;
+use kas_7guis::config::Config;
+use kas_7guis::data::Shared;
+use kas_7guis::demos::{Demo, DEMOS};
+use kas_7guis::theme::{Scale, THEMES};
+use kas_7guis::{cells, crud, kiosk, launcher, logs, theme};
 
-use kas::prelude::*;
-use kas::widgets::dialog::MessageBox;
-use kas::widgets::Button;
+/// Command-line arguments
+#[derive(Debug, Default)]
+struct Args {
+    /// Open this demo instead of the launcher
+    demo: Option<&'static Demo>,
+    /// Open demos as tabs within the launcher
+    tabs: bool,
+    /// Cycle through the demos instead of opening the launcher
+    kiosk: bool,
+    /// Print detailed `tracing` output
+    trace: bool,
+    /// Theme name, overriding the config
+    theme: Option<&'static str>,
+    /// UI scale (percent), overriding the config
+    scale: Option<u32>,
+    /// Font size (pt), overriding the scale
+    font_size: Option<f32>,
+    /// Open Cells filled with this many thousand generated cells
+    stress: Option<u32>,
+    /// Open CRUD with this many generated entries
+    entries: Option<usize>,
+}
 
-#[derive(Clone, Debug)]
-enum X {
-    Counter,
-    Temp,
-    Flight,
-    Timer,
-    Crud,
-    Circle,
-    Cells,
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args = Args::default();
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--demo" => {
+                    let name = iter.next().ok_or("--demo: expected a demo name")?;
+                    let demo = Demo::find(&name)
+                        .ok_or_else(|| format!("--demo: unknown demo `{name}`"))?;
+                    if !demo.is_available() {
+                        return Err(format!("--demo: `{name}` is not implemented yet"));
+                    }
+                    args.demo = Some(demo);
+                }
+                "--tabs" => args.tabs = true,
+                "--kiosk" => args.kiosk = true,
+                "--trace" => args.trace = true,
+                "--theme" => {
+                    let name = iter.next().ok_or("--theme: expected a theme name")?;
+                    let (_, name) = THEMES
+                        .iter()
+                        .find(|(_, n)| *n == name)
+                        .ok_or_else(|| format!("--theme: unknown theme `{name}`"))?;
+                    args.theme = Some(*name);
+                }
+                "--scale" => {
+                    let value = iter.next().ok_or("--scale: expected a percentage")?;
+                    let scale = value.trim_end_matches('%').parse::<u32>().ok();
+                    let scale = scale.filter(|s| *s > 0);
+                    args.scale =
+                        Some(scale.ok_or_else(|| format!("--scale: bad value `{value}`"))?);
+                }
+                "--font-size" => {
+                    let value = iter.next().ok_or("--font-size: expected a size (pt)")?;
+                    let size = value.parse::<f32>().ok().filter(|s| *s > 0.0);
+                    let size = size.ok_or_else(|| format!("--font-size: bad value `{value}`"))?;
+                    args.font_size = Some(size);
+                }
+                "--stress" => {
+                    let value = iter
+                        .next()
+                        .ok_or("--stress: expected a number (thousands)")?;
+                    let n = value.parse::<u32>().ok().filter(|n| *n > 0);
+                    args.stress = Some(n.ok_or_else(|| format!("--stress: bad value `{value}`"))?);
+                }
+                "--entries" => {
+                    let value = iter.next().ok_or("--entries: expected a number")?;
+                    let n = value.parse::<usize>().ok();
+                    args.entries =
+                        Some(n.ok_or_else(|| format!("--entries: bad value `{value}`"))?);
+                }
+                _ => return Err(format!("unexpected argument `{arg}`")),
+            }
+        }
+        Ok(args)
+    }
 }
 
 fn main() -> Result<(), kas::app::Error> {
-    env_logger::init();
+    logs::init();
 
-    let ui = impl_anon! {
-        #[widget {
-            layout = column! [
-                Button::label_msg("&Counter", X::Counter),
-                Button::label_msg("Tem&perature Converter", X::Temp),
-                Button::label_msg("&Flight &Booker", X::Flight),
-                Button::label_msg("&Timer", X::Timer),
-                Button::label_msg("CRUD (Create, Read, &Update and &Delete)", X::Crud),
-                Button::label_msg("Ci&rcle Drawer", X::Circle),
-                Button::label_msg("Ce&lls", X::Cells),
-            ];
-        }]
-        struct {
-            core: widget_core!(),
+    let args = Args::parse().unwrap_or_else(|msg| {
+        eprintln!("Error: {msg}");
+        eprintln!("Usage: kas-7guis [--tabs] [--kiosk] [--trace] [--demo NAME]");
+        eprintln!("         [--theme NAME] [--scale PERCENT] [--font-size PT]");
+        eprintln!("         [--stress THOUSANDS] [--entries N]");
+        let themes: Vec<_> = THEMES.iter().map(|(_, name)| *name).collect();
+        eprintln!("Themes: {}", themes.join(", "));
+        eprintln!("Demos:");
+        for demo in DEMOS.iter().filter(|demo| demo.is_available()) {
+            eprintln!("  {:<10}{}", demo.name, demo.description);
         }
-        impl Events for Self {
-            type Data = ();
+        std::process::exit(2);
+    });
+    if args.trace {
+        logs::init_tracing();
+    }
 
-            fn handle_messages(&mut self, cx: &mut EventCx, _: &Self::Data) {
-                if let Some(x) = cx.try_pop() {
-                    cx.add_window(match x {
-                        X::Counter => counter::window(),
-                        X::Temp => temp_conv::window(),
-                        X::Flight => flight_booker::window(),
-                        X::Timer => timer::window(),
-                        X::Crud => crud::window(),
-                        X::Cells => cells::window(),
-                        _ => MessageBox::new("Not implemented yet!").into_window("TODO"),
-                    });
-                }
-            }
-        }
+    let config = Config::load();
+
+    let window = if let Some(thousands) = args.stress {
+        cells::stress_window(thousands)
+    } else if let Some(n) = args.entries {
+        crud::large_window(n)
+    } else if let Some(window) = args.demo.and_then(|demo| demo.window()) {
+        window
+    } else if args.kiosk {
+        kiosk::window()
+    } else {
+        launcher::window(args.tabs)
     };
-    let window = Window::new(ui, "7GUIs Launcher");
 
-    let theme = kas::theme::FlatTheme::new();
+    // Overrides apply to this run only; they are not saved to the config
+    let mut theme = theme::build(&config);
+    if let Some(name) = args.theme {
+        let _ = theme.set_theme(name);
+    }
+    if let Some(scale) = args.scale {
+        let _ = theme.set_font_size(Scale(scale).font_size());
+    }
+    if let Some(size) = args.font_size {
+        let _ = theme.set_font_size(size);
+    }
     kas::app::Default::with_theme(theme)
-        .build(())?
+        .build(Shared {
+            config,
+            ..Default::default()
+        })?
         .with(window)
         .run()
 }
@@ -0,0 +1,179 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Standard menu bar for demo windows
+
+use kas::prelude::*;
+use kas::widgets::menu::MenuBar;
+use kas::widgets::Popup;
+
+use crate::data::Shared;
+use crate::shortcuts::{Shortcut, ShortcutSheet};
+use crate::{about, theme};
+
+/// Demo-specific menu actions and help
+///
+/// Since the menu bar is not a parent of the demo's UI, actions should push
+/// messages handled by the application data ([`Shared`]).
+#[derive(Clone, Copy, Debug)]
+pub struct MenuSpec {
+    /// File → Open and File → Save, sent to the demo as [`FileAction`]
+    pub open_save: bool,
+    /// File → Export…
+    pub export: Option<fn(&mut EventCx)>,
+    /// Edit → Undo
+    pub undo: Option<fn(&mut EventCx)>,
+    /// Edit → Redo
+    pub redo: Option<fn(&mut EventCx)>,
+    /// Key bindings, listed by Help → Keyboard shortcuts
+    pub shortcuts: &'static [Shortcut],
+}
+
+impl MenuSpec {
+    /// No demo-specific actions
+    pub const NONE: MenuSpec = MenuSpec {
+        open_save: false,
+        export: None,
+        undo: None,
+        redo: None,
+        shortcuts: &[],
+    };
+}
+
+/// A File menu action for the demo's own document
+///
+/// These are sent to the widget registered with [`MenuTarget`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileAction {
+    Open,
+    Save,
+}
+
+/// An Edit menu action for the demo's own document
+///
+/// Like [`FileAction`], this is sent to the widget registered with
+/// [`MenuTarget`]. Use as `undo: Some(|cx| cx.push(EditAction::Undo))`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditAction {
+    Undo,
+}
+
+/// Registers widget `.0` as the receiver of [`FileAction`]s and [`EditAction`]s
+///
+/// A demo using [`MenuSpec::open_save`] or [`EditAction`] should send this to
+/// itself on configure; the message is handled by the enclosing demo frame.
+#[derive(Debug)]
+pub struct MenuTarget(pub Id);
+
+#[derive(Clone, Copy, Debug)]
+enum Item {
+    Open,
+    Save,
+    Export,
+    Close,
+    Undo,
+    Redo,
+    Shortcuts,
+    About,
+}
+
+impl_scope! {
+    /// Menu bar: File, Edit (if used), View and Help menus
+    #[widget {
+        Data = ();
+        layout = self.bar;
+    }]
+    pub struct WindowMenu {
+        core: widget_core!(),
+        #[widget] bar: MenuBar<()>,
+        #[widget] sheet: Popup<ShortcutSheet>,
+        spec: MenuSpec,
+    }
+
+    impl Self {
+        /// Construct, with demo-specific actions from `spec`
+        pub fn new(spec: MenuSpec) -> Self {
+            let bar = MenuBar::builder()
+                .menu("&File", |menu| {
+                    if spec.open_save {
+                        menu.entry("&Open", Item::Open);
+                        menu.entry("&Save", Item::Save);
+                    }
+                    if spec.export.is_some() {
+                        menu.entry("&Export…", Item::Export);
+                    }
+                    menu.entry("&Close", Item::Close);
+                });
+            let bar = if spec.undo.is_some() || spec.redo.is_some() {
+                bar.menu("&Edit", |menu| {
+                    if spec.undo.is_some() {
+                        menu.entry("&Undo", Item::Undo);
+                    }
+                    if spec.redo.is_some() {
+                        menu.entry("&Redo", Item::Redo);
+                    }
+                })
+            } else {
+                bar
+            };
+            let bar = bar
+                .menu("&View", theme::add_submenus)
+                .menu("&Help", |menu| {
+                    menu.entry("&Keyboard shortcuts", Item::Shortcuts);
+                    menu.entry("&About", Item::About);
+                })
+                .build();
+
+            WindowMenu {
+                core: Default::default(),
+                bar,
+                sheet: Popup::new(ShortcutSheet::new(spec.shortcuts), Direction::Down),
+                spec,
+            }
+        }
+
+        /// Show the keyboard shortcut cheat sheet
+        pub fn show_shortcuts(&mut self, cx: &mut EventCx) {
+            self.sheet.open(cx, &(), self.id(), true);
+        }
+    }
+
+    impl Events for Self {
+        fn handle_messages(&mut self, cx: &mut EventCx, _: &()) {
+            theme::handle_messages(cx);
+
+            if let Some(item) = cx.try_pop() {
+                let action = match item {
+                    Item::Open => {
+                        cx.push(FileAction::Open);
+                        None
+                    }
+                    Item::Save => {
+                        cx.push(FileAction::Save);
+                        None
+                    }
+                    Item::Export => self.spec.export,
+                    Item::Close => {
+                        cx.action(self.id(), Action::CLOSE);
+                        None
+                    }
+                    Item::Undo => self.spec.undo,
+                    Item::Redo => self.spec.redo,
+                    Item::Shortcuts => {
+                        self.show_shortcuts(cx);
+                        None
+                    }
+                    Item::About => {
+                        cx.add_window::<Shared>(about::window());
+                        None
+                    }
+                };
+                if let Some(action) = action {
+                    action(cx);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Command palette: fuzzy search over a list of actions
+
+use kas::prelude::*;
+use kas::widgets::{AccessLabel, Button, Column, EditBox, EditField, EditGuard};
+use std::fmt::Debug;
+
+/// Maximum number of matches shown
+const MAX_SHOWN: usize = 8;
+
+/// An action offered by the [`Palette`]
+pub struct Entry {
+    label: String,
+    run: Box<dyn Fn(&mut EventCx)>,
+}
+
+impl Entry {
+    /// An entry which pushes `msg` when run
+    pub fn msg<M: Clone + Debug + 'static>(label: impl ToString, msg: M) -> Self {
+        Entry {
+            label: label.to_string(),
+            run: Box::new(move |cx| cx.push(msg.clone())),
+        }
+    }
+}
+
+/// Emitted by the [`Palette`] after running an entry
+///
+/// The parent should close the palette. This is pushed after the entry's own
+/// message, so it is popped first.
+#[derive(Clone, Debug)]
+pub struct Done;
+
+#[derive(Clone, Debug)]
+struct SetQuery(String);
+
+#[derive(Clone, Debug)]
+struct Run(usize);
+
+#[derive(Clone, Debug)]
+struct RunFirst;
+
+#[derive(Clone, Debug)]
+struct QueryGuard;
+impl EditGuard for QueryGuard {
+    type Data = ();
+
+    fn activate(_: &mut EditField<Self>, cx: &mut EventCx, _: &()) -> IsUsed {
+        cx.push(RunFirst);
+        IsUsed::Used
+    }
+
+    fn edit(edit: &mut EditField<Self>, cx: &mut EventCx, _: &()) {
+        cx.push(SetQuery(edit.get_string()));
+    }
+}
+
+/// Score `text` as a match for `query`, if it matches at all
+///
+/// Characters of `query` (ignoring case and spaces) must appear in order in
+/// `text`. Matches at the start of a word or directly following the previous
+/// match score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut start = 0;
+    let mut prev = None;
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = start + text[start..].iter().position(|t| *t == c)?;
+        score += 1;
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        if index > 0 && prev == Some(index - 1) {
+            score += 2;
+        }
+        prev = Some(index);
+        start = index + 1;
+    }
+    Some(score)
+}
+
+impl_scope! {
+    /// A search box over a list of [`Entry`]
+    ///
+    /// Press Enter to run the best match or click any shown match.
+    #[widget {
+        Data = ();
+        layout = column! [self.query, self.list];
+    }]
+    pub struct Palette {
+        core: widget_core!(),
+        #[widget] query: EditBox<QueryGuard>,
+        #[widget] list: Column<Button<AccessLabel>>,
+        entries: Vec<Entry>,
+        /// Indices of entries shown in `list`, best first
+        shown: Vec<usize>,
+    }
+
+    impl Self {
+        /// Construct over the given entries
+        pub fn new(entries: Vec<Entry>) -> Self {
+            Palette {
+                core: Default::default(),
+                query: EditBox::new(QueryGuard),
+                list: Column::new(vec![]),
+                entries,
+                shown: vec![],
+            }
+        }
+
+        /// Clear the query, showing the first entries
+        pub fn reset(&mut self, cx: &mut EventCx) {
+            let act = self.query.set_string(String::new());
+            cx.action(self.query.id(), act);
+            self.set_query(cx, "");
+        }
+
+        fn set_query(&mut self, cx: &mut EventCx, query: &str) {
+            let mut matches: Vec<(u32, usize)> = self
+                .entries
+                .iter()
+                .enumerate()
+                .filter_map(|(i, entry)| fuzzy_score(query, &entry.label).map(|s| (s, i)))
+                .collect();
+            // Stable sort: equal scores keep their order in `entries`
+            matches.sort_by(|a, b| b.0.cmp(&a.0));
+            self.shown = matches.into_iter().take(MAX_SHOWN).map(|(_, i)| i).collect();
+
+            for index in (0..self.list.len()).rev() {
+                self.list.remove(cx, index);
+            }
+            for index in self.shown.iter().copied() {
+                let button = Button::label_msg(self.entries[index].label.clone(), Run(index));
+                self.list.push(&mut cx.config_cx(), &(), button);
+            }
+        }
+
+        fn run(&mut self, cx: &mut EventCx, index: usize) {
+            (self.entries[index].run)(cx);
+            cx.push(Done);
+        }
+    }
+
+    impl Events for Self {
+        fn handle_messages(&mut self, cx: &mut EventCx, _: &()) {
+            if let Some(SetQuery(query)) = cx.try_pop() {
+                self.set_query(cx, &query);
+            } else if let Some(Run(index)) = cx.try_pop() {
+                self.run(cx, index);
+            } else if let Some(RunFirst) = cx.try_pop() {
+                if let Some(index) = self.shown.first().copied() {
+                    self.run(cx, index);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Live reload of the config file
+//!
+//! Edits to the config file (e.g. in a text editor) are applied to running
+//! windows: theme settings immediately and other settings when next read.
+
+use kas::prelude::*;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+use crate::config::{Config, ConfigMsg};
+use crate::data::Shared;
+use crate::theme;
+
+/// Timer used to check for file events
+const POLL_TIMER: u64 = 0;
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watches the config file, reporting events over a channel
+struct FileWatch {
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    path: PathBuf,
+}
+
+impl FileWatch {
+    fn new() -> Option<Self> {
+        let path = Config::path()?;
+        // Watch the directory since editors often replace the file
+        let dir = path.parent()?;
+        let (sender, events) = channel();
+        let result = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        });
+        match result {
+            Ok(watcher) => Some(FileWatch {
+                _watcher: watcher,
+                events,
+                path,
+            }),
+            Err(err) => {
+                log::warn!("unable to watch {}: {err}", dir.display());
+                None
+            }
+        }
+    }
+
+    /// True if the config file changed since the last call
+    fn changed(&self) -> bool {
+        let mut changed = false;
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) => {
+                    let name = self.path.file_name();
+                    changed |= event.paths.iter().any(|path| path.file_name() == name);
+                }
+                Err(err) => log::warn!("config watch error: {err}"),
+            }
+        }
+        changed
+    }
+}
+
+impl_scope! {
+    /// Reloads the config when the file changes
+    ///
+    /// This should wrap the contents of the application's first window (only).
+    /// Saving the config also triggers a reload, which finds nothing changed.
+    #[widget {
+        Data = Shared;
+        layout = self.inner;
+    }]
+    pub struct Reload<W: Widget<Data = Shared>> {
+        core: widget_core!(),
+        #[widget] inner: W,
+        watch: Option<FileWatch>,
+    }
+
+    impl Self {
+        /// Construct around `inner`
+        pub fn new(inner: W) -> Self {
+            Reload {
+                core: Default::default(),
+                inner,
+                watch: None,
+            }
+        }
+    }
+
+    impl Events for Self {
+        fn configure(&mut self, cx: &mut ConfigCx) {
+            if self.watch.is_none() {
+                self.watch = FileWatch::new();
+            }
+            if self.watch.is_some() {
+                cx.request_timer(self.id(), POLL_TIMER, POLL_INTERVAL);
+            }
+        }
+
+        fn handle_event(&mut self, cx: &mut EventCx, data: &Shared, event: Event) -> IsUsed {
+            match event {
+                Event::Timer(POLL_TIMER) => {
+                    let changed = self.watch.as_ref().is_some_and(|watch| watch.changed());
+                    let config = changed.then(Config::reload).flatten();
+                    if let Some(config) = config.filter(|config| *config != data.config) {
+                        log::info!("config file changed; reloading");
+                        theme::apply_config(cx, &config);
+                        cx.push(ConfigMsg::Reload(Box::new(config)));
+                    }
+                    cx.request_timer(self.id(), POLL_TIMER, POLL_INTERVAL);
+                    IsUsed::Used
+                }
+                _ => IsUsed::Unused,
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Keyboard shortcut cheat sheet
+//!
+//! Each demo lists its own bindings in [`crate::menu::MenuSpec::shortcuts`];
+//! these are shown, followed by [`COMMON`], on F1 or `?`.
+
+use kas::event::{Command, ElementState, Key};
+use kas::prelude::*;
+use kas::widgets::{Column, Label};
+
+/// A key binding, for display
+#[derive(Clone, Copy, Debug)]
+pub struct Shortcut {
+    /// Keys, e.g. "Ctrl+Z"
+    pub keys: &'static str,
+    /// Description of the action
+    pub action: &'static str,
+}
+
+impl Shortcut {
+    pub const fn new(keys: &'static str, action: &'static str) -> Self {
+        Shortcut { keys, action }
+    }
+}
+
+/// Bindings available in all windows
+pub const COMMON: &[Shortcut] = &[
+    Shortcut::new("F1, ?", "Show keyboard shortcuts"),
+    Shortcut::new("Tab, Shift+Tab", "Move keyboard focus"),
+    Shortcut::new("Alt", "Show access keys (underlined)"),
+    Shortcut::new("Alt+F, Alt+V, Alt+H", "Open the File, View or Help menu"),
+    Shortcut::new("Escape", "Close a menu or popup"),
+];
+
+/// True if `event` should show the cheat sheet: F1 or `?`
+///
+/// Call this on events not used by the focused widget, so that `?` may
+/// still be typed into edit fields.
+pub fn is_help_event(event: &Event) -> bool {
+    match event {
+        Event::Command(Command::Help, _) => true,
+        Event::Key(event, _) => {
+            event.state == ElementState::Pressed
+                && matches!(event.logical_key.as_ref(), Key::Character("?"))
+        }
+        _ => false,
+    }
+}
+
+impl_scope! {
+    /// Lists shortcuts in two columns: keys and action
+    #[widget {
+        Data = ();
+        layout = row! [self.keys, self.actions];
+    }]
+    pub struct ShortcutSheet {
+        core: widget_core!(),
+        #[widget] keys: Column<Label<&'static str>>,
+        #[widget] actions: Column<Label<&'static str>>,
+    }
+
+    impl Self {
+        /// Construct, listing `shortcuts` then [`COMMON`]
+        pub fn new(shortcuts: &'static [Shortcut]) -> Self {
+            let all = || shortcuts.iter().chain(COMMON);
+            ShortcutSheet {
+                core: Default::default(),
+                keys: Column::new(all().map(|s| Label::new(s.keys)).collect()),
+                actions: Column::new(all().map(|s| Label::new(s.action)).collect()),
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Debug snapshots of demo state
+//!
+//! Pressing Ctrl+Shift+D in Cells, CRUD or Timer saves the demo's model as
+//! JSON in the temporary directory; the path is shown as a toast. Attach the
+//! file when reporting a bug.
+
+use kas::event::{ElementState, EventState, Key};
+use kas::prelude::*;
+use serde::Serialize;
+
+use crate::toast::Toast;
+
+/// True if `event` is the snapshot shortcut, Ctrl+Shift+D
+pub fn is_dump_event(cx: &EventState, event: &Event) -> bool {
+    let modifiers = cx.modifiers();
+    match event {
+        Event::Key(event, _) if event.state == ElementState::Pressed => {
+            let is_d = match event.logical_key.as_ref() {
+                Key::Character(c) => c.eq_ignore_ascii_case("d"),
+                _ => false,
+            };
+            is_d && modifiers.control_key() && modifiers.shift_key()
+        }
+        _ => false,
+    }
+}
+
+/// Save `state` of demo `name` to a new file, reporting the path as a toast
+pub fn dump(cx: &mut EventCx, name: &str, state: &impl Serialize) {
+    let time = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = std::env::temp_dir().join(format!("kas-7guis-{name}-{time}.json"));
+    let result = serde_json::to_string_pretty(state)
+        .map_err(|err| err.to_string())
+        .and_then(|text| std::fs::write(&path, text).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => cx.push(Toast(format!("State saved to {}", path.display()))),
+        Err(err) => cx.push(Toast(format!("Failed to save state: {err}"))),
+    }
+}
+
+/// Request to save a snapshot, pushed by [`DumpKey`]
+#[derive(Clone, Debug)]
+pub struct Dump;
+
+impl_scope! {
+    /// Pushes [`Dump`] on Ctrl+Shift+D within `inner`
+    ///
+    /// This is for demos built on [`kas::widgets::Adapt`], which handles the
+    /// message with access to its state.
+    #[widget {
+        layout = self.inner;
+    }]
+    pub struct DumpKey<W: Widget> {
+        core: widget_core!(),
+        #[widget] inner: W,
+    }
+
+    impl Self {
+        /// Construct around `inner`
+        pub fn new(inner: W) -> Self {
+            DumpKey {
+                core: Default::default(),
+                inner,
+            }
+        }
+    }
+
+    impl Events for Self {
+        type Data = W::Data;
+
+        fn handle_event(&mut self, cx: &mut EventCx, _: &Self::Data, event: Event) -> IsUsed {
+            if is_dump_event(cx, &event) {
+                cx.push(Dump);
+                IsUsed::Used
+            } else {
+                IsUsed::Unused
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Status bar shared by demos
+
+use kas::prelude::*;
+use kas::widgets::{Label, ProgressBar, Text};
+
+/// Update the enclosing [`StatusBar`]
+#[derive(Clone, Debug)]
+pub enum Status {
+    /// Set the message text
+    Message(String),
+    /// Set progress in the range 0 to 1, or `None` when idle
+    Progress(Option<f32>),
+}
+
+impl_scope! {
+    /// A bottom row below `inner`: a message, progress and key hints
+    ///
+    /// The message and progress are set by [`Status`] messages from `inner`.
+    #[widget {
+        layout = column! [
+            self.inner,
+            row! [self.message, self.progress, self.hint],
+        ];
+    }]
+    pub struct StatusBar<W: Widget> {
+        core: widget_core!(),
+        #[widget] inner: W,
+        #[widget(&self.text)] message: Text<String, String>,
+        #[widget(&self.fraction)] progress: ProgressBar<Option<f32>, kas::dir::Right>,
+        #[widget(&())] hint: Label<&'static str>,
+        text: String,
+        fraction: Option<f32>,
+    }
+
+    impl Self {
+        /// Construct around `inner`, with a fixed key `hint`
+        pub fn new(inner: W, hint: &'static str) -> Self {
+            StatusBar {
+                core: Default::default(),
+                inner,
+                message: Text::new(|_, text: &String| text.clone()),
+                progress: ProgressBar::right(|_, fraction: &Option<f32>| fraction.unwrap_or(0.0)),
+                hint: Label::new(hint),
+                text: String::new(),
+                fraction: None,
+            }
+        }
+    }
+
+    impl Events for Self {
+        type Data = W::Data;
+
+        fn handle_messages(&mut self, cx: &mut EventCx, _: &Self::Data) {
+            if let Some(status) = cx.try_pop() {
+                match status {
+                    Status::Message(text) => {
+                        self.text = text;
+                        cx.update(self.message.as_node(&self.text));
+                    }
+                    Status::Progress(fraction) => {
+                        self.fraction = fraction;
+                        cx.update(self.progress.as_node(&self.fraction));
+                    }
+                }
+            }
+        }
+    }
+}
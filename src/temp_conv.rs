@@ -4,50 +4,445 @@
 //     https://www.apache.org/licenses/LICENSE-2.0
 
 //! Temperature converter
+//!
+//! This doubles as a small generic converter: a category selector swaps the
+//! table of units (and labels) used by the two fields.
 
+use kas::draw::color::Rgba;
+use kas::geom::Quad;
 use kas::prelude::*;
-use kas::widgets::{Adapt, EditBox};
+use kas::theme::{Background, FrameStyle};
+use kas::view::{Driver, ListView};
+use kas::widgets::{Adapt, Button, ComboBox, ScrollBars, Text};
+
+use crate::clipboard;
+use crate::data::Shared;
+use crate::demos::demo_window;
+use crate::menu::MenuSpec;
+use crate::shortcuts::Shortcut;
+use crate::widgets::{format_number, NumberActivated, NumberEdited, NumberError};
+use crate::widgets::{NumberField, NumberGuard};
+
+/// A unit, defined by an affine map to the category's base unit
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Unit {
+    name: &'static str,
+    /// Base value = `value * scale + offset`
+    scale: f64,
+    offset: f64,
+}
+impl Unit {
+    const fn new(name: &'static str, scale: f64, offset: f64) -> Self {
+        Unit {
+            name,
+            scale,
+            offset,
+        }
+    }
+
+    fn to_base(&self, value: f64) -> f64 {
+        value * self.scale + self.offset
+    }
+
+    fn from_base(&self, base: f64) -> f64 {
+        (base - self.offset) / self.scale
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Category {
+    #[default]
+    Temperature,
+    Length,
+    Mass,
+}
+impl Category {
+    /// Left and right units
+    fn units(self) -> [Unit; 2] {
+        match self {
+            Category::Temperature => [
+                Unit::new("Celsius", 1.0, 0.0),
+                Unit::new("Fahrenheit", 5.0 / 9.0, -32.0 * (5.0 / 9.0)),
+            ],
+            Category::Length => [
+                Unit::new("Metres", 1.0, 0.0),
+                Unit::new("Feet", 0.3048, 0.0),
+            ],
+            Category::Mass => [
+                Unit::new("Kilograms", 1.0, 0.0),
+                Unit::new("Pounds", 0.45359237, 0.0),
+            ],
+        }
+    }
+
+    /// Lowest meaningful value (in base units), with a description
+    fn lower_bound(self) -> Option<(f64, &'static str)> {
+        match self {
+            Category::Temperature => Some((-273.15, "absolute zero")),
+            Category::Length | Category::Mass => None,
+        }
+    }
+}
+
+/// Convert degrees Celsius to Fahrenheit
+pub fn celsius_to_fahrenheit(value: f64) -> f64 {
+    let [c, f] = Category::Temperature.units();
+    f.from_base(c.to_base(value))
+}
+
+/// Convert degrees Fahrenheit to Celsius
+pub fn fahrenheit_to_celsius(value: f64) -> f64 {
+    let [c, f] = Category::Temperature.units();
+    c.from_base(f.to_base(value))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
+impl Side {
+    fn index(self) -> usize {
+        match self {
+            Side::Left => 0,
+            Side::Right => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Error {
+    #[default]
+    None,
+    Parse(&'static str),
+    BelowBound {
+        what: &'static str,
+        bound: f64,
+        unit: &'static str,
+    },
+}
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::None => Ok(()),
+            Error::Parse(unit) => f.write_fmt(format_args!("Error: {unit} value is not a number")),
+            Error::BelowBound { what, bound, unit } => f.write_fmt(format_args!(
+                "Error: value is below {what} ({bound:.2} {unit})"
+            )),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 enum Message {
-    FromCelsius(f64),
-    FromFahrenheit(f64),
+    Set(Side, f64),
+    Invalid(Error),
+    /// Record the current conversion in the history
+    Commit,
+    ClearHistory,
+    Swap,
+    /// Adjust a value by `step` in the given direction (true for up)
+    Nudge(Side, bool),
+}
+
+/// Copy the current conversion to the clipboard
+#[derive(Clone, Debug)]
+struct CopyConversion;
+
+/// Increment used by the spin buttons
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Step {
+    Tenth,
+    #[default]
+    One,
+    Ten,
+}
+impl Step {
+    fn value(self) -> f64 {
+        match self {
+            Step::Tenth => 0.1,
+            Step::One => 1.0,
+            Step::Ten => 10.0,
+        }
+    }
 }
 
 impl_scope! {
     #[impl_default]
     #[derive(Debug)]
-    struct Temperature {
-        celsius: f64 = 0.0,
-        fahrenheit: f64 = 32.0,
+    struct Converter {
+        category: Category,
+        values: [f64; 2] = [0.0, 32.0],
+        step: Step,
+        error: Error,
+        history: Vec<String>,
     }
 
     impl Self {
+        fn unit(&self, side: Side) -> Unit {
+            self.category.units()[side.index()]
+        }
+
+        fn get(&self, side: Side) -> f64 {
+            self.values[side.index()]
+        }
+
+        /// Lower and upper limits for the `side` field
+        fn limits(&self, side: Side) -> (f64, f64) {
+            let unit = self.unit(side);
+            let min = self.category.lower_bound().map(|(bound, _)| unit.from_base(bound));
+            (min.unwrap_or(f64::NEG_INFINITY), f64::INFINITY)
+        }
+
+        /// Describe an input error for the `side` field
+        fn input_error(&self, side: Side, error: NumberError) -> Error {
+            let unit = self.unit(side).name;
+            match error {
+                NumberError::Parse | NumberError::AboveMax(_) => Error::Parse(unit),
+                NumberError::BelowMin(bound) => Error::BelowBound {
+                    what: self.category.lower_bound().map(|(_, what)| what).unwrap_or_default(),
+                    bound,
+                    unit,
+                },
+            }
+        }
+
+        /// Check `value` against the category's lower bound
+        fn check(&self, side: Side, value: f64) -> Result<f64, Error> {
+            let unit = self.unit(side);
+            if let Some((bound, what)) = self.category.lower_bound() {
+                if unit.to_base(value) < bound {
+                    return Err(Error::BelowBound {
+                        what,
+                        bound: unit.from_base(bound),
+                        unit: unit.name,
+                    });
+                }
+            }
+            Ok(value)
+        }
+
+        /// Describe the current conversion, e.g. "0 Celsius = 32 Fahrenheit"
+        fn describe(&self) -> String {
+            let [left, right] = self.category.units();
+            format!(
+                "{} {} = {} {}",
+                format_number(self.values[0]),
+                left.name,
+                format_number(self.values[1]),
+                right.name
+            )
+        }
+
+        fn set(&mut self, side: Side, value: f64) {
+            let base = self.unit(side).to_base(value);
+            for (unit, v) in self.category.units().iter().zip(self.values.iter_mut()) {
+                *v = unit.from_base(base);
+            }
+            // Keep the input exactly as given
+            self.values[side.index()] = value;
+        }
+
+        fn set_category(&mut self, category: Category) {
+            self.category = category;
+            self.error = Error::None;
+            self.set(Side::Left, 0.0);
+        }
+
         fn handle(&mut self, msg: Message) {
+            let _span = tracing::trace_span!("temp_conv::handle", ?msg).entered();
+            self.error = Error::None;
             match msg {
-                Message::FromCelsius(c) => {
-                    self.celsius = c;
-                    self.fahrenheit = c * (9.0/5.0) + 32.0;
+                Message::Set(side, value) => self.set(side, value),
+                Message::Invalid(error) => self.error = error,
+                Message::Commit => self.history.push(self.describe()),
+                Message::ClearHistory => self.history.clear(),
+                Message::Swap => {
+                    // Re-interpret the right value in the left unit
+                    match self.check(Side::Left, self.values[1]) {
+                        Ok(value) => self.set(Side::Left, value),
+                        Err(error) => self.error = error,
+                    }
                 }
-                Message::FromFahrenheit(f) => {
-                    self.celsius = (f - 32.0) * (5.0 / 9.0);
-                    self.fahrenheit = f;
+                Message::Nudge(side, up) => {
+                    let step = if up { self.step.value() } else { -self.step.value() };
+                    match self.check(side, self.get(side) + step) {
+                        Ok(value) => self.set(side, value),
+                        Err(error) => self.error = error,
+                    }
                 }
             }
         }
     }
 }
 
-pub fn window() -> Window<()> {
-    let ui = kas::row![
-        EditBox::instant_parser(|temp: &Temperature| temp.celsius, Message::FromCelsius),
-        "Celsius =",
-        EditBox::instant_parser(
-            |temp: &Temperature| temp.fahrenheit,
-            Message::FromFahrenheit
+/// Entry field for the value on `side`
+///
+/// Input is converted whenever valid. Invalid input (including values below
+/// the category's lower bound) sets the field's error state and is reported
+/// below the row. Pressing Enter on valid input adds the conversion to the
+/// history.
+fn value_field(side: Side) -> NumberField<Converter, Side> {
+    let guard = match side {
+        Side::Left => NumberGuard::new(side, |conv: &Converter| conv.get(Side::Left))
+            .with_limits(|conv| conv.limits(Side::Left)),
+        Side::Right => NumberGuard::new(side, |conv: &Converter| conv.get(Side::Right))
+            .with_limits(|conv| conv.limits(Side::Right)),
+    };
+    NumberField::new(guard)
+}
+
+/// Celsius values mapped to the ends of the gauge
+const GAUGE_MIN: f64 = -40.0;
+const GAUGE_MAX: f64 = 60.0;
+
+impl_scope! {
+    /// A thermometer-style gauge showing the current Celsius value
+    ///
+    /// The bar is coloured on a blue→red gradient. Nothing is drawn outside
+    /// of the temperature category.
+    #[impl_default]
+    #[widget]
+    struct Gauge {
+        core: widget_core!(),
+        /// Fill fraction in `0.0..=1.0`, if any
+        fraction: Option<f32>,
+    }
+
+    impl Layout for Self {
+        fn size_rules(&mut self, sizer: SizeCx, axis: AxisInfo) -> SizeRules {
+            let dpem = sizer.dpem();
+            if axis.is_horizontal() {
+                let min = (dpem * 6.0).cast_nearest();
+                let ideal = (dpem * 12.0).cast_nearest();
+                SizeRules::new(min, ideal, (0, 0), Stretch::High)
+            } else {
+                SizeRules::fixed((dpem * 0.8).cast_nearest(), (0, 0))
+            }
+        }
+
+        fn draw(&mut self, mut draw: DrawCx) {
+            let rect = self.rect();
+            draw.frame(rect, FrameStyle::Frame, Background::Default);
+            if let Some(frac) = self.fraction {
+                let mut bar = rect;
+                bar.size.0 = (rect.size.0 as f32 * frac).cast_nearest();
+                let cold = Rgba::rgb(0.1, 0.3, 0.9);
+                let hot = Rgba::rgb(0.9, 0.15, 0.1);
+                let col = Rgba::rgb(
+                    cold.r + (hot.r - cold.r) * frac,
+                    cold.g + (hot.g - cold.g) * frac,
+                    cold.b + (hot.b - cold.b) * frac,
+                );
+                draw.draw_device().rect(Quad::conv(bar), col);
+            }
+        }
+    }
+
+    impl Events for Self {
+        type Data = Converter;
+
+        fn update(&mut self, cx: &mut ConfigCx, conv: &Converter) {
+            let fraction = (conv.category == Category::Temperature).then(|| {
+                let c = conv.get(Side::Left);
+                ((c - GAUGE_MIN) / (GAUGE_MAX - GAUGE_MIN)).clamp(0.0, 1.0) as f32
+            });
+            if fraction != self.fraction {
+                self.fraction = fraction;
+                cx.redraw(self);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HistoryDriver;
+impl Driver<String, Vec<String>> for HistoryDriver {
+    type Widget = Text<String, String>;
+    fn make(&mut self, _: &usize) -> Self::Widget {
+        Text::new(|_, entry: &String| entry.clone())
+    }
+}
+
+/// Tiny +/− buttons nudging the value on `side`
+fn spin_buttons(side: Side) -> impl Widget<Data = Converter> {
+    kas::column![
+        Button::label_msg("+", Message::Nudge(side, true)),
+        Button::label_msg("−", Message::Nudge(side, false)),
+    ]
+    .map_any()
+}
+
+pub fn ui() -> impl Widget<Data = Shared> {
+    type HistoryView = ListView<Vec<String>, HistoryDriver, kas::dir::Down>;
+    let history =
+        ScrollBars::new(HistoryView::new(HistoryDriver)).map(|conv: &Converter| &conv.history);
+
+    let ui = kas::column![
+        ComboBox::new(
+            [
+                ("Temperature", Category::Temperature),
+                ("Length", Category::Length),
+                ("Mass", Category::Mass),
+            ],
+            |_, conv: &Converter| conv.category
         ),
-        "Fahrenheit",
+        row![
+            spin_buttons(Side::Left),
+            value_field(Side::Left),
+            Text::new(|_, conv: &Converter| format!("{} =", conv.unit(Side::Left).name)),
+            Button::label_msg("⇄", Message::Swap).map_any(),
+            spin_buttons(Side::Right),
+            value_field(Side::Right),
+            Text::new(|_, conv: &Converter| conv.unit(Side::Right).name.to_string()),
+            Button::label_msg("&Copy", CopyConversion).map_any(),
+        ],
+        row![
+            "Step:",
+            ComboBox::new(
+                [("0.1", Step::Tenth), ("1", Step::One), ("10", Step::Ten)],
+                |_, conv: &Converter| conv.step
+            ),
+        ],
+        Gauge::default(),
+        Text::new(|_, conv: &Converter| format!("{}", conv.error)),
+        "History:",
+        history,
+        Button::label_msg("C&lear history", Message::ClearHistory).map_any(),
     ];
-    let ui = Adapt::new(ui, Temperature::default()).on_message(|_, temp, msg| temp.handle(msg));
-    Window::new(ui, "Temperature Converter")
+    let ui = Adapt::new(ui, Converter::default())
+        .on_message(|_, conv, category| conv.set_category(category))
+        .on_message(|_, conv, step| conv.step = step)
+        .on_message(|_, conv, msg| conv.handle(msg))
+        .on_message(|cx, conv, CopyConversion| clipboard::set_text(cx, conv.describe()))
+        .on_message(|_, conv, edited: NumberEdited<Side>| {
+            let msg = match edited.result {
+                Ok(value) => Message::Set(edited.tag, value),
+                Err(error) => Message::Invalid(conv.input_error(edited.tag, error)),
+            };
+            conv.handle(msg);
+        })
+        .on_message(|_, conv, _: NumberActivated<Side>| conv.handle(Message::Commit));
+    ui.map_any()
+}
+
+/// Menu actions
+pub const MENU: MenuSpec = MenuSpec {
+    shortcuts: &[
+        Shortcut::new(
+            "Enter",
+            "Add the conversion to the history (in a value field)",
+        ),
+        Shortcut::new("Alt+C", "Copy the conversion"),
+        Shortcut::new("Alt+L", "Clear the history"),
+    ],
+    ..MenuSpec::NONE
+};
+
+/// Title of the demo's window
+pub const WINDOW_TITLE: &str = "Temperature Converter";
+
+/// Construct the demo in its own window
+pub fn window() -> Window<Shared> {
+    demo_window(ui(), MENU, WINDOW_TITLE)
 }
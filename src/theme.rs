@@ -0,0 +1,201 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Theme and colour scheme selection
+
+use kas::draw::color::Rgba8Srgb;
+use kas::event::EventState;
+use kas::prelude::*;
+use kas::theme::{ColorsSrgb, FlatTheme, MultiTheme, SimpleTheme, Theme, ThemeControl};
+use kas::wgpu::ShadedTheme;
+use kas::widgets::menu::SubMenuBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, ConfigMsg};
+
+/// Available themes, as (menu label, name)
+pub const THEMES: [(&str, &str); 3] = [
+    ("&Flat", "flat"),
+    ("&Simple", "simple"),
+    ("S&haded", "shaded"),
+];
+
+/// Switch to the named theme
+#[derive(Clone, Debug)]
+pub struct SetTheme(pub &'static str);
+
+impl SetTheme {
+    /// Apply to the theme, affecting all windows
+    pub fn apply(&self, cx: &mut EventState) {
+        cx.adjust_theme(|theme| theme.set_theme(self.0));
+    }
+}
+
+/// Name of the bundled high-contrast colour scheme
+const HIGH_CONTRAST: &str = "high-contrast";
+
+/// White on black with a yellow accent, for low-vision users
+///
+/// kas themes do not allow the width of focus outlines to be configured, so
+/// instead the navigation focus uses the strongest available colour.
+const HIGH_CONTRAST_COLORS: ColorsSrgb = ColorsSrgb {
+    is_dark: true,
+    background: Rgba8Srgb::rgb(0x00, 0x00, 0x00),
+    frame: Rgba8Srgb::rgb(0xff, 0xff, 0xff),
+    accent: Rgba8Srgb::rgb(0xff, 0xff, 0x00),
+    accent_soft: Rgba8Srgb::rgb(0x80, 0x80, 0x00),
+    nav_focus: Rgba8Srgb::rgb(0x00, 0xff, 0xff),
+    edit_bg: Rgba8Srgb::rgb(0x00, 0x00, 0x00),
+    edit_bg_disabled: Rgba8Srgb::rgb(0x30, 0x30, 0x30),
+    edit_bg_error: Rgba8Srgb::rgb(0xa0, 0x00, 0x00),
+    text: Rgba8Srgb::rgb(0xff, 0xff, 0xff),
+    text_invert: Rgba8Srgb::rgb(0x00, 0x00, 0x00),
+    text_disabled: Rgba8Srgb::rgb(0xa0, 0xa0, 0xa0),
+    text_sel_bg: Rgba8Srgb::rgb(0x00, 0x60, 0xff),
+};
+
+/// Add our bundled colour schemes to `theme`
+fn with_schemes<T>(mut theme: T) -> T
+where
+    T: Theme<kas::wgpu::DrawPipe<()>, Config = kas::theme::Config>,
+{
+    let mut config = theme.config().into_owned();
+    config.set_color_scheme(HIGH_CONTRAST, HIGH_CONTRAST_COLORS);
+    let _ = theme.apply_config(&config);
+    theme
+}
+
+/// Construct the application theme, as configured
+pub fn build(config: &Config) -> MultiTheme {
+    let mut theme = MultiTheme::builder()
+        .add("flat", with_schemes(FlatTheme::new()))
+        .add("simple", with_schemes(SimpleTheme::new()))
+        .add("shaded", with_schemes(ShadedTheme::new()))
+        .build();
+    // The returned actions are irrelevant before the app starts
+    let _ = theme.set_theme(&config.theme);
+    let _ = theme.set_scheme(config.color_mode.scheme_name());
+    let _ = theme.set_font_size(Scale(config.scale).font_size());
+    theme
+}
+
+/// Apply theme, colour and scale settings from `config`, affecting all windows
+pub fn apply_config(cx: &mut EventState, config: &Config) {
+    cx.adjust_theme(|theme| {
+        theme.set_theme(&config.theme)
+            | theme.set_scheme(config.color_mode.scheme_name())
+            | theme.set_font_size(Scale(config.scale).font_size())
+    });
+}
+
+/// Font size (pt) at 100% scale
+const BASE_FONT_SIZE: f32 = 10.0;
+
+/// UI scale, in percent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scale(pub u32);
+
+impl Scale {
+    /// Entries for a [`kas::widgets::ComboBox`]
+    pub const ENTRIES: [(&'static str, Scale); 6] = [
+        ("75%", Scale(75)),
+        ("100%", Scale(100)),
+        ("125%", Scale(125)),
+        ("150%", Scale(150)),
+        ("175%", Scale(175)),
+        ("200%", Scale(200)),
+    ];
+
+    /// Font size achieving this scale
+    ///
+    /// All sizes in kas themes are derived from the font size.
+    pub fn font_size(self) -> f32 {
+        BASE_FONT_SIZE * self.0 as f32 / 100.0
+    }
+
+    /// Apply to the theme, affecting all windows
+    pub fn apply(self, cx: &mut EventState) {
+        cx.adjust_theme(|theme| theme.set_font_size(self.font_size()));
+    }
+}
+
+/// Light or dark colours
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Follow the system preference
+    #[default]
+    System,
+    Light,
+    Dark,
+    #[serde(rename = "high-contrast")]
+    HighContrast,
+}
+
+impl ColorMode {
+    /// Entries for a [`kas::widgets::ComboBox`]
+    pub const ENTRIES: [(&'static str, ColorMode); 4] = [
+        ("Follow system", ColorMode::System),
+        ("Light", ColorMode::Light),
+        ("Dark", ColorMode::Dark),
+        ("High contrast", ColorMode::HighContrast),
+    ];
+
+    /// Name of the kas colour scheme to use
+    pub fn scheme_name(self) -> &'static str {
+        match self {
+            ColorMode::System => match dark_light::detect() {
+                dark_light::Mode::Dark => "dark",
+                dark_light::Mode::Light | dark_light::Mode::Default => "light",
+            },
+            ColorMode::Light => "light",
+            ColorMode::Dark => "dark",
+            ColorMode::HighContrast => HIGH_CONTRAST,
+        }
+    }
+
+    /// Apply to the theme, affecting all windows
+    pub fn apply(self, cx: &mut EventState) {
+        cx.adjust_theme(|theme| theme.set_scheme(self.scheme_name()));
+    }
+}
+
+/// Add Theme, Colours and Scale submenus to `menu`
+///
+/// Entries emit messages handled by [`handle_messages`].
+pub fn add_submenus<Data>(menu: &mut SubMenuBuilder<Data>) {
+    menu.submenu("&Theme", |menu| {
+        for (label, name) in THEMES {
+            menu.entry(label, SetTheme(name));
+        }
+    });
+    menu.submenu("&Colours", |menu| {
+        for (label, mode) in ColorMode::ENTRIES {
+            menu.entry(label, mode);
+        }
+    });
+    menu.submenu("&Scale", |menu| {
+        for (label, scale) in Scale::ENTRIES {
+            menu.entry(label, scale);
+        }
+    });
+}
+
+/// Apply [`SetTheme`], [`ColorMode`] and [`Scale`] messages
+///
+/// Changes affect all windows and are saved to the config (by pushing a
+/// [`ConfigMsg`]).
+pub fn handle_messages(cx: &mut EventCx) {
+    if let Some(theme) = cx.try_pop::<SetTheme>() {
+        theme.apply(cx);
+        cx.push(ConfigMsg::Theme(theme.0));
+    } else if let Some(mode) = cx.try_pop::<ColorMode>() {
+        mode.apply(cx);
+        cx.push(ConfigMsg::ColorMode(mode));
+    } else if let Some(scale) = cx.try_pop::<Scale>() {
+        scale.apply(cx);
+        cx.push(ConfigMsg::Scale(scale.0));
+    }
+}
@@ -4,45 +4,545 @@
 //     https://www.apache.org/licenses/LICENSE-2.0
 
 //! Timer
+//!
+//! The timer counts up to the chosen duration, or (in countdown mode) down
+//! from it to zero, flashing the progress bar and showing a notice on
+//! completion.
+//!
+//! With feature `notifications`, completion may also raise a desktop
+//! notification; this is off unless enabled in the UI or the config:
+//! ```toml
+//! [demos.timer]
+//! notify = true
+//! ```
+//!
+//! In stopwatch mode the timer counts up without limit, started and stopped
+//! by the Start/Stop button. The duration controls are disabled, and the
+//! progress bar sweeps once per second instead of showing progress.
+//!
+//! "Lap" records the elapsed time in a list below, with the time since the
+//! previous lap.
+//!
+//! The maximum duration (default 30 seconds, at most a day) and the step of
+//! the duration slider (default 0.1 seconds) may be set in seconds:
+//! ```toml
+//! [demos.timer]
+//! max_duration = 5400
+//! step = 0.01
+//! ```
+//! Times are shown as `m:ss.t` from a maximum of a minute, and `h:mm:ss` from
+//! an hour. "High precision" adds hundredths of a second.
+//!
+//! Rather than waking every frame, the timer sleeps until the shown time next
+//! changes or the progress bar next grows by a pixel, so that long timers do
+//! not busy-poll.
 
 use kas::prelude::*;
-use kas::widgets::{label_any, Adapt, Button, ProgressBar, Slider, Text};
+use kas::widgets::{
+    label_any, Adapt, Button, CheckButton, Column, ProgressBar, ScrollBars, Slider, Text,
+};
+use std::cell::Cell;
+use std::rc::Rc;
 use std::time::{Duration, Instant};
 
+use crate::animation::{Tween, FRAME};
+use crate::data::Shared;
+use crate::demos::demo_window;
+use crate::menu::MenuSpec;
+use crate::shortcuts::Shortcut;
+use crate::snapshot::{self, Dump, DumpKey};
+use crate::toast::Toast;
+use crate::widgets::{NumberEdited, NumberField, NumberGuard};
+
 const DUR_MIN: Duration = Duration::from_secs(0);
+/// Default maximum duration
 const DUR_MAX: Duration = Duration::from_secs(30);
+/// Largest configurable maximum duration
+const DUR_MAX_LIMIT: Duration = Duration::from_secs(24 * 3600);
+/// Default step of the duration slider
 const DUR_STEP: Duration = Duration::from_millis(100);
+/// Precision of a typed duration, and the finest configurable step
+const TYPED_STEP: Duration = Duration::from_millis(1);
+/// Keyboard step of the slider, as a fraction of the maximum
+const SLIDER_STEP: f64 = 0.01;
 const TIMER_ID: u64 = 0;
-const TIMER_SLEEP: Duration = DUR_STEP;
+/// Wake-up interval while animating; otherwise see `Data::next_wake`
+const TIMER_SLEEP: Duration = FRAME;
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(3600);
+/// Time taken by the progress bar to catch up after a reset or change
+const EASE_DURATION: Duration = Duration::from_millis(300);
+/// Time for which the progress bar flashes when a countdown completes
+const FLASH_DURATION: Duration = Duration::from_secs(2);
+/// Time for which the flashing progress bar is full (then empty)
+const FLASH_PERIOD: Duration = Duration::from_millis(250);
 
 #[derive(Clone, Debug)]
 struct ActionReset;
 
-pub fn window() -> Window<()> {
+/// Pause or resume the timer
+#[derive(Clone, Debug)]
+struct ActionPause;
+
+/// Record the elapsed time as a lap
+#[derive(Clone, Debug)]
+struct ActionLap;
+
+#[derive(Clone, Debug)]
+struct ActionClearLaps;
+
+/// Set the duration to this fraction of the maximum (from the slider)
+#[derive(Clone, Debug)]
+struct SetFraction(f64);
+
+/// Stopwatch mode (true) or timer (false)
+#[derive(Clone, Debug)]
+struct SetStopwatch(bool);
+
+/// Show hundredths of a second (true) or not (false)
+#[derive(Clone, Debug)]
+struct SetPrecise(bool);
+
+/// Format `time` in a style suited to durations up to `max`
+///
+/// This is `h:mm:ss` when `max` is an hour or more, `m:ss.t` when it is a
+/// minute or more, otherwise seconds to a tenth. If `precise`, seconds are
+/// shown to a hundredth instead.
+fn format_time(time: Duration, max: Duration, precise: bool) -> String {
+    let secs = time.as_secs();
+    let fraction = if precise {
+        format!(".{:02}", time.subsec_millis() / 10)
+    } else if max >= HOUR {
+        String::new()
+    } else {
+        format!(".{}", time.subsec_millis() / 100)
+    };
+    if max >= HOUR {
+        format!(
+            "{}:{:02}:{:02}{fraction}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        )
+    } else if max >= MINUTE {
+        format!("{}:{:02}{fraction}", secs / 60, secs % 60)
+    } else {
+        format!("{secs}{fraction}s")
+    }
+}
+
+/// The smallest change in time shown by [`format_time`]
+fn resolution(max: Duration, precise: bool) -> Duration {
+    if precise {
+        Duration::from_millis(10)
+    } else if max >= HOUR {
+        Duration::from_secs(1)
+    } else {
+        Duration::from_millis(100)
+    }
+}
+
+/// The remainder of `time` after a whole number of (non-zero) `period`s
+fn remainder(time: Duration, period: Duration) -> Duration {
+    let nanos = time.as_nanos() % period.as_nanos();
+    Duration::from_nanos(nanos as u64)
+}
+
+/// Round `time` to the nearest multiple of `step`
+fn round_to(time: Duration, step: Duration) -> Duration {
+    step.mul_f64((time.as_secs_f64() / step.as_secs_f64()).round())
+}
+
+/// Enable (true) or disable desktop notifications on completion
+#[derive(Clone, Debug)]
+struct SetNotify(bool);
+
+/// Raise a desktop notification that a timer of `duration` has completed
+#[cfg(feature = "notifications")]
+fn notify_complete(duration: Duration) {
+    let result = notify_rust::Notification::new()
+        .summary("Timer complete")
+        .body(&format!(
+            "{} have elapsed",
+            format_time(duration, duration, false)
+        ))
+        .sound_name("alarm-clock-elapsed")
+        .show();
+    if let Err(err) = result {
+        log::warn!("failed to show notification: {err}");
+    }
+}
+
+/// Desktop notifications require feature `notifications`
+#[cfg(not(feature = "notifications"))]
+fn notify_complete(_: Duration) {}
+
+/// Count down (true) or up (false)
+#[derive(Clone, Debug)]
+struct SetCountdown(bool);
+
+pub fn ui() -> impl Widget<Data = Shared> {
     #[derive(Debug)]
     struct Data {
         duration: Duration,
+        /// Maximum duration
+        max: Duration,
+        /// Step of the duration slider
+        step: Duration,
         elapsed: Duration,
+        /// Time elapsed before [`Self::start`]
+        ///
+        /// Elapsed time is measured from a fixed start rather than summed
+        /// over timer ticks, so pausing and resuming does not drift.
+        accumulated: Duration,
+        /// Start of the current run; `None` when paused or complete
         start: Option<Instant>,
+        paused: bool,
+        /// Show remaining rather than elapsed time
+        countdown: bool,
+        /// Count up without limit, ignoring the duration
+        stopwatch: bool,
+        /// The progress bar flashes until this time
+        flash_until: Option<Instant>,
+        /// Elapsed time of each lap, in order
+        laps: Vec<Duration>,
+        /// Raise a desktop notification on completion
+        notify: bool,
+        /// Show hundredths of a second
+        precise: bool,
+        /// Width of the progress bar in pixels, set by [`BarWidth`]
+        bar_width: Rc<Cell<i32>>,
+        /// True once settings have been read from the config
+        configured: bool,
+        /// Displayed progress, following [`Self::fraction`]
+        progress: Tween,
     }
+    impl Data {
+        fn fraction(&self) -> f32 {
+            if self.duration.is_zero() {
+                1.0
+            } else {
+                self.elapsed.as_secs_f32() / self.duration.as_secs_f32()
+            }
+        }
+
+        /// Update the elapsed time of a running timer, stopping when complete
+        ///
+        /// Returns true if the timer has just completed.
+        fn tick(&mut self) -> bool {
+            if let Some(start) = self.start {
+                let elapsed = self.accumulated + (Instant::now() - start);
+                if self.stopwatch {
+                    self.elapsed = elapsed;
+                    return false;
+                }
+                self.elapsed = self.duration.min(elapsed);
+                if self.elapsed >= self.duration {
+                    self.start = None;
+                    self.accumulated = self.elapsed;
+                    return true;
+                }
+            }
+            false
+        }
+
+        /// The time shown: elapsed, or remaining in countdown mode
+        fn shown_time(&self) -> Duration {
+            if self.countdown && !self.stopwatch {
+                self.duration.saturating_sub(self.elapsed)
+            } else {
+                self.elapsed
+            }
+        }
+
+        /// Value of the progress bar
+        fn bar_value(&self) -> f32 {
+            if let Some(until) = self.flash_until {
+                let left = until.saturating_duration_since(Instant::now());
+                let phase = left.as_millis() / FLASH_PERIOD.as_millis();
+                return if phase % 2 == 0 { 1.0 } else { 0.0 };
+            }
+            if self.stopwatch {
+                return self.elapsed.subsec_millis() as f32 / 1000.0;
+            }
+            let value = self.progress.value();
+            if self.countdown {
+                1.0 - value
+            } else {
+                value
+            }
+        }
+
+        /// Continue from the current elapsed time
+        fn run(&mut self) {
+            self.accumulated = self.elapsed;
+            self.start = Some(Instant::now());
+        }
+
+        /// Set the duration, resuming the timer if now incomplete
+        fn set_duration(&mut self, duration: Duration) {
+            self.duration = duration;
+            if self.start.is_some() {
+                let _ = self.tick();
+            } else if self.paused {
+                self.elapsed = self.elapsed.min(duration);
+                self.accumulated = self.elapsed;
+            } else if self.elapsed < self.duration {
+                self.run();
+            }
+            self.progress.set(self.fraction());
+        }
+
+        /// Pause a running timer, or resume a paused one
+        fn toggle_pause(&mut self) {
+            if self.paused {
+                self.paused = false;
+                if self.stopwatch || self.elapsed < self.duration {
+                    self.run();
+                }
+            } else {
+                let _ = self.tick();
+                self.start = None;
+                self.accumulated = self.elapsed;
+                self.paused = true;
+            }
+        }
+
+        /// True while the timer or progress bar is moving
+        fn is_active(&self) -> bool {
+            self.start.is_some() || self.progress.is_running() || self.flash_until.is_some()
+        }
+
+        /// Switch between stopwatch and timer, starting again from zero
+        ///
+        /// The stopwatch starts stopped; the timer starts running.
+        fn set_stopwatch(&mut self, stopwatch: bool) {
+            self.stopwatch = stopwatch;
+            self.elapsed = Duration::ZERO;
+            self.accumulated = Duration::ZERO;
+            self.paused = stopwatch;
+            self.start = (!stopwatch).then(Instant::now);
+            self.flash_until = None;
+            self.progress.jump(0.0);
+        }
 
-    let ui = kas::grid! {
-        (0, 0) => "Elapsed time:",
-        (1, 0) => ProgressBar::right(|_, data: &Data| data.elapsed.as_secs_f32() / data.duration.as_secs_f32()),
-        (1, 1) => Text::new(|_, data: &Data| {
-            format!("{}.{}s", data.elapsed.as_secs(), data.elapsed.subsec_millis() / 100)
+        /// The maximum time, which selects the style of [`format_time`]
+        ///
+        /// The stopwatch has no maximum, so its style follows the elapsed time.
+        fn format_max(&self) -> Duration {
+            if self.stopwatch {
+                self.elapsed.max(MINUTE)
+            } else {
+                self.max
+            }
+        }
+
+        /// Format `time` as configured
+        fn format(&self, time: Duration) -> String {
+            format_time(time, self.format_max(), self.precise)
+        }
+
+        /// Time until the display next changes, but at least a frame
+        ///
+        /// While the progress bar animates, this is a frame. Otherwise it is
+        /// the time until the shown time changes at its resolution or the bar
+        /// grows by a pixel, whichever is sooner.
+        fn next_wake(&self) -> Duration {
+            if self.start.is_none() || self.progress.is_running() || self.flash_until.is_some() {
+                return TIMER_SLEEP;
+            }
+            let resolution = resolution(self.format_max(), self.precise);
+            let text = if self.countdown {
+                // The remaining time is truncated, so changes on reaching a
+                // multiple of the resolution
+                let rem = remainder(self.shown_time(), resolution);
+                if rem.is_zero() {
+                    resolution
+                } else {
+                    rem
+                }
+            } else {
+                resolution - remainder(self.elapsed, resolution)
+            };
+            let width = u32::try_from(self.bar_width.get()).unwrap_or(0).max(1);
+            // The stopwatch's bar sweeps once per second
+            let sweep = if self.stopwatch {
+                Duration::from_secs(1)
+            } else {
+                self.duration
+            };
+            let pixel = (sweep / width).max(TIMER_SLEEP);
+            let bar = pixel - remainder(self.elapsed, pixel);
+            let mut wake = text.min(bar);
+            if !self.stopwatch {
+                wake = wake.min(self.duration.saturating_sub(self.elapsed));
+            }
+            wake.max(TIMER_SLEEP)
+        }
+    }
+
+    /// Text of lap `index`, with the time since the previous lap
+    fn lap_row(index: usize) -> Box<dyn Widget<Data = Data>> {
+        Box::new(Text::new(move |_, data: &Data| {
+            let Some(lap) = data.laps.get(index) else {
+                return String::new();
+            };
+            let prev = index
+                .checked_sub(1)
+                .map(|i| data.laps[i])
+                .unwrap_or_default();
+            let delta = data.format(lap.saturating_sub(prev));
+            format!("Lap {}: {} (+{delta})", index + 1, data.format(*lap))
+        }))
+    }
+
+    impl_scope! {
+        /// The list of laps
+        ///
+        /// Laps are only appended or cleared, so rows are added one at a time.
+        #[impl_default]
+        #[widget {
+            layout = self.rows;
+        }]
+        struct Laps {
+            core: widget_core!(),
+            #[widget] rows: Column<Box<dyn Widget<Data = Data>>> = Column::new(vec![]),
+        }
+        impl Events for Self {
+            type Data = Data;
+
+            fn update(&mut self, cx: &mut ConfigCx, data: &Data) {
+                while self.rows.len() > data.laps.len() {
+                    self.rows.pop(cx);
+                }
+                while self.rows.len() < data.laps.len() {
+                    let row = lap_row(self.rows.len());
+                    self.rows.push(cx, data, row);
+                }
+            }
+        }
+    }
+
+    impl_scope! {
+        /// Records the width of the progress bar in [`Data::bar_width`]
+        #[widget]
+        struct BarWidth<W: Widget<Data = Data>> {
+            core: widget_core!(),
+            #[widget] bar: W,
+            width: Rc<Cell<i32>>,
+        }
+        impl Layout for Self {
+            fn size_rules(&mut self, sizer: SizeCx, axis: AxisInfo) -> SizeRules {
+                self.bar.size_rules(sizer, axis)
+            }
+
+            fn set_rect(&mut self, cx: &mut ConfigCx, rect: Rect) {
+                self.core.rect = rect;
+                self.width.set(rect.size.0);
+                self.bar.set_rect(cx, rect);
+            }
+
+            fn find_id(&mut self, coord: Coord) -> Option<Id> {
+                self.bar.find_id(coord)
+            }
+
+            fn draw(&mut self, mut draw: DrawCx) {
+                draw.recurse(&mut self.bar);
+            }
+        }
+        impl Events for Self {
+            type Data = Data;
+        }
+    }
+
+    let bar_width = Rc::new(Cell::new(0));
+    let bar = BarWidth {
+        core: Default::default(),
+        bar: ProgressBar::right(|_, data: &Data| data.bar_value()),
+        width: bar_width.clone(),
+    };
+
+    let ui = DumpKey::new(kas::grid! {
+        (0, 0) => Text::new(|_, data: &Data| {
+            let label = if data.countdown { "Remaining time:" } else { "Elapsed time:" };
+            label.to_string()
         }),
+        (1, 0) => bar,
+        (1, 1) => Text::new(|_, data: &Data| data.format(data.shown_time())),
+        (0, 1) => kas::column![
+            CheckButton::new_msg(
+                "Count &down",
+                |_, data: &Data| data.countdown,
+                SetCountdown
+            )
+            .on_update(|cx, _, data: &Data| cx.set_disabled(data.stopwatch)),
+            CheckButton::new_msg(
+                "&Stopwatch",
+                |_, data: &Data| data.stopwatch,
+                SetStopwatch
+            ),
+        ],
         (0, 2) => "Duration:",
-        (1, 2) => Slider::right(DUR_MIN..=DUR_MAX, |_, data: &Data| data.duration)
-                    .with_step(DUR_STEP)
-                    .with_msg(|value| value),
-        (0..2, 3) => Button::new_msg(label_any("Reset"), ActionReset),
-    };
+        (1, 2) => Slider::right(0.0..=1.0, |_, data: &Data| {
+                        data.duration.as_secs_f64() / data.max.as_secs_f64()
+                    })
+                    .with_step(SLIDER_STEP)
+                    .with_msg(SetFraction)
+                    .on_update(|cx, _, data: &Data| cx.set_disabled(data.stopwatch)),
+        (0, 3) => "Seconds:",
+        (1, 3) => NumberField::new(
+            NumberGuard::new((), |data: &Data| data.duration.as_secs_f64())
+                .with_limits(|data| (DUR_MIN.as_secs_f64(), data.max.as_secs_f64()))
+                .with_step(TYPED_STEP.as_secs_f64())
+        )
+        .on_update(|cx, _, data: &Data| cx.set_disabled(data.stopwatch)),
+        (0, 4) => Button::new_msg(
+            Text::new(|_, data: &Data| {
+                let label = match (data.stopwatch, data.paused) {
+                    (true, true) => "Start",
+                    (true, false) => "Stop",
+                    (false, true) => "Resume",
+                    (false, false) => "Pause",
+                };
+                label.to_string()
+            }),
+            ActionPause
+        ),
+        (1, 4) => Button::new_msg(label_any("Reset"), ActionReset),
+        (0, 5) => CheckButton::new_msg(
+            "&Notify on completion",
+            |_, data: &Data| data.notify,
+            SetNotify
+        )
+        .on_update(|cx, _, _: &Data| cx.set_disabled(!cfg!(feature = "notifications"))),
+        (1, 5) => CheckButton::new_msg(
+            "High &precision",
+            |_, data: &Data| data.precise,
+            SetPrecise
+        ),
+        (0, 6) => Button::new_msg(label_any("Lap"), ActionLap),
+        (1, 6) => Button::new_msg(label_any("Clear laps"), ActionClearLaps)
+                    .on_update(|cx, _, data: &Data| cx.set_disabled(data.laps.is_empty())),
+        (0..2, 7) => ScrollBars::new(Laps::default()),
+    });
 
     let data = Data {
         duration: Duration::from_secs(10),
+        max: DUR_MAX,
+        step: DUR_STEP,
         elapsed: Duration::default(),
+        accumulated: Duration::default(),
         start: None,
+        paused: false,
+        countdown: false,
+        stopwatch: false,
+        flash_until: None,
+        laps: vec![],
+        notify: false,
+        precise: false,
+        bar_width,
+        configured: false,
+        progress: Tween::new(0.0, EASE_DURATION),
     };
 
     let ui = Adapt::new(ui, data)
@@ -50,35 +550,127 @@ pub fn window() -> Window<()> {
             data.start = Some(Instant::now());
             cx.request_timer(TIMER_ID, TIMER_SLEEP);
         })
-        .on_timer(TIMER_ID, |cx, data, _| {
-            if let Some(start) = data.start {
-                data.elapsed = data.duration.min(Instant::now() - start);
-                if data.elapsed < data.duration {
-                    cx.request_timer(TIMER_ID, TIMER_SLEEP);
-                } else {
-                    data.start = None;
+        .on_update(|_, data, shared: &Shared| {
+            if !data.configured {
+                data.configured = true;
+                let config = &shared.config;
+                let secs = |key| {
+                    let secs = config.demo_option("timer", key);
+                    secs.and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                };
+                if let Some(max) = secs("max_duration") {
+                    data.max = max.clamp(Duration::from_secs(1), DUR_MAX_LIMIT);
                 }
-                true
-            } else {
-                false
+                if let Some(step) = secs("step") {
+                    data.step = step.clamp(TYPED_STEP, data.max);
+                }
+                data.duration = data.duration.min(data.max);
+                if let Some(dur) = secs("duration") {
+                    data.duration = dur.clamp(DUR_MIN, data.max);
+                }
+                data.notify = config.demo_option("timer", "notify").unwrap_or(false);
             }
         })
-        .on_message(|cx, data, dur| {
-            data.duration = dur;
-            if let Some(start) = data.start {
-                data.elapsed = data.duration.min(Instant::now() - start);
-                if data.elapsed >= data.duration {
-                    data.start = None;
+        .on_timer(TIMER_ID, |cx, data, _| {
+            let was_active = data.is_active();
+            if data.tick() {
+                if data.countdown {
+                    data.flash_until = Some(Instant::now() + FLASH_DURATION);
+                    cx.push(Toast("Time's up!".to_string()));
                 }
-            } else if data.elapsed < data.duration {
-                data.start = Some(Instant::now() - data.elapsed);
+                if data.notify {
+                    notify_complete(data.duration);
+                }
+            }
+            if data
+                .flash_until
+                .is_some_and(|until| until <= Instant::now())
+            {
+                data.flash_until = None;
+            }
+            data.progress.retarget(data.fraction());
+            if data.is_active() {
+                cx.request_timer(TIMER_ID, data.next_wake());
+            }
+            was_active
+        })
+        .on_message(|cx, data, SetFraction(fraction)| {
+            let dur = round_to(data.max.mul_f64(fraction), data.step);
+            data.set_duration(dur.min(data.max));
+            cx.request_timer(TIMER_ID, Duration::ZERO);
+        })
+        .on_message(|cx, data, edited: NumberEdited<()>| {
+            if let Ok(secs) = edited.result {
+                data.set_duration(Duration::from_secs_f64(secs));
                 cx.request_timer(TIMER_ID, Duration::ZERO);
             }
         })
         .on_message(|cx, data, ActionReset| {
-            data.start = Some(Instant::now());
+            // A paused timer stays paused, at zero
+            data.elapsed = Duration::ZERO;
+            data.accumulated = Duration::ZERO;
+            data.flash_until = None;
+            data.start = (!data.paused).then(Instant::now);
+            data.progress.set(0.0);
             cx.request_timer(TIMER_ID, TIMER_SLEEP);
+        })
+        .on_message(|_, data, ActionLap| data.laps.push(data.elapsed))
+        .on_message(|_, data, ActionClearLaps| data.laps.clear())
+        .on_message(|_, data, SetNotify(notify)| data.notify = notify)
+        .on_message(|cx, data, SetPrecise(precise)| {
+            data.precise = precise;
+            cx.request_timer(TIMER_ID, Duration::ZERO);
+        })
+        .on_message(|cx, data, SetStopwatch(stopwatch)| {
+            data.set_stopwatch(stopwatch);
+            cx.request_timer(TIMER_ID, Duration::ZERO);
+        })
+        .on_message(|cx, data, SetCountdown(countdown)| {
+            data.countdown = countdown;
+            data.flash_until = None;
+            cx.request_timer(TIMER_ID, Duration::ZERO);
+        })
+        .on_message(|cx, data, ActionPause| {
+            data.toggle_pause();
+            cx.request_timer(TIMER_ID, Duration::ZERO);
+        })
+        .on_message(|cx, data, Dump| {
+            let state = serde_json::json!({
+                "duration": data.duration.as_secs_f64(),
+                "max_duration": data.max.as_secs_f64(),
+                "step": data.step.as_secs_f64(),
+                "elapsed": data.elapsed.as_secs_f64(),
+                "running": data.start.is_some(),
+                "paused": data.paused,
+                "countdown": data.countdown,
+                "stopwatch": data.stopwatch,
+                "precise": data.precise,
+                "laps": data.laps.iter().map(|lap| lap.as_secs_f64()).collect::<Vec<_>>(),
+                "progress": data.progress.value(),
+                "configured": data.configured,
+            });
+            snapshot::dump(cx, "timer", &state);
         });
 
-    Window::new(ui, "Timer")
+    ui
+}
+
+/// Menu actions
+pub const MENU: MenuSpec = MenuSpec {
+    shortcuts: &[
+        Shortcut::new("Alt+D", "Count down (or up)"),
+        Shortcut::new("Alt+S", "Stopwatch mode"),
+        Shortcut::new("Alt+N", "Notify on completion (feature notifications)"),
+        Shortcut::new("Alt+P", "Show hundredths of a second"),
+        Shortcut::new("Ctrl+Shift+D", "Save a debug snapshot"),
+    ],
+    ..MenuSpec::NONE
+};
+
+/// Title of the demo's window
+pub const WINDOW_TITLE: &str = "Timer";
+
+/// Construct the demo in its own window
+pub fn window() -> Window<Shared> {
+    demo_window(ui(), MENU, WINDOW_TITLE)
 }
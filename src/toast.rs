@@ -0,0 +1,90 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Transient notifications
+//!
+//! Any demo may `cx.push(Toast(..))`; the message is shown by the enclosing
+//! [`Toasts`] widget (see [`crate::demos::Demo::widget`]).
+
+use kas::prelude::*;
+use kas::widgets::Text;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Time for which each toast is shown
+const DURATION: Duration = Duration::from_secs(4);
+
+/// Timer used to expire toasts
+const EXPIRE_TIMER: u64 = 0;
+
+/// A message to show briefly to the user
+#[derive(Clone, Debug)]
+pub struct Toast(pub String);
+
+impl_scope! {
+    /// Shows [`Toast`] messages from `inner` below it, for a few seconds each
+    #[widget {
+        layout = column! [self.inner, self.text];
+    }]
+    pub struct Toasts<W: Widget> {
+        core: widget_core!(),
+        #[widget] inner: W,
+        #[widget(&self.shown)] text: Text<String, String>,
+        /// Active toasts with their expiry time, oldest first
+        toasts: VecDeque<(Instant, String)>,
+        shown: String,
+    }
+
+    impl Self {
+        /// Construct around `inner`
+        pub fn new(inner: W) -> Self {
+            Toasts {
+                core: Default::default(),
+                inner,
+                text: Text::new(|_, shown: &String| shown.clone()),
+                toasts: VecDeque::new(),
+                shown: String::new(),
+            }
+        }
+
+        fn update_shown(&mut self, cx: &mut EventCx) {
+            let lines: Vec<&str> = self.toasts.iter().map(|(_, s)| s.as_str()).collect();
+            self.shown = lines.join("\n");
+            cx.update(self.text.as_node(&self.shown));
+        }
+    }
+
+    impl Events for Self {
+        type Data = W::Data;
+
+        fn handle_event(&mut self, cx: &mut EventCx, _: &Self::Data, event: Event) -> IsUsed {
+            match event {
+                Event::Timer(EXPIRE_TIMER) => {
+                    let now = Instant::now();
+                    while self.toasts.front().is_some_and(|(expiry, _)| *expiry <= now) {
+                        self.toasts.pop_front();
+                    }
+                    if let Some((expiry, _)) = self.toasts.front() {
+                        cx.request_timer(self.id(), EXPIRE_TIMER, *expiry - now);
+                    }
+                    self.update_shown(cx);
+                    IsUsed::Used
+                }
+                _ => IsUsed::Unused,
+            }
+        }
+
+        fn handle_messages(&mut self, cx: &mut EventCx, _: &Self::Data) {
+            if let Some(Toast(msg)) = cx.try_pop() {
+                log::info!("{msg}");
+                self.toasts.push_back((Instant::now() + DURATION, msg));
+                if self.toasts.len() == 1 {
+                    cx.request_timer(self.id(), EXPIRE_TIMER, DURATION);
+                }
+                self.update_shown(cx);
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Shared undo support
+
+/// A stack of previous states
+///
+/// Before each undoable change, push the state being replaced; [`Self::undo`]
+/// then returns these states in reverse order.
+#[derive(Debug)]
+pub struct UndoStack<T> {
+    states: Vec<T>,
+}
+
+impl<T> Default for UndoStack<T> {
+    fn default() -> Self {
+        UndoStack { states: vec![] }
+    }
+}
+
+impl<T> UndoStack<T> {
+    /// Record `prev`, the state prior to some change
+    pub fn push(&mut self, prev: T) {
+        self.states.push(prev);
+    }
+
+    /// True if there is anything to undo
+    pub fn can_undo(&self) -> bool {
+        !self.states.is_empty()
+    }
+
+    /// Take the most recently pushed state, if any
+    pub fn undo(&mut self) -> Option<T> {
+        self.states.pop()
+    }
+}
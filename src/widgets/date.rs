@@ -0,0 +1,284 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Date entry
+
+use chrono::{Duration, Months, NaiveDate, ParseError};
+use kas::event::Command;
+use kas::prelude::*;
+use kas::widgets::{EditBox, EditField, EditGuard};
+use serde::Deserialize;
+use std::fmt::Debug;
+
+/// Format used to enter and display dates
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Parse a date in [`DATE_FORMAT`], ignoring surrounding white-space
+pub fn parse_date(text: &str) -> Result<NaiveDate, ParseError> {
+    NaiveDate::parse_from_str(text.trim(), DATE_FORMAT)
+}
+
+/// Format a date using [`DATE_FORMAT`]
+pub fn format_date(date: NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+/// A format for entering and displaying dates
+///
+/// In config files, this is one of `"iso"`, `"dotted"` or `"locale"`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateFormat {
+    /// [`DATE_FORMAT`]
+    #[default]
+    Iso,
+    /// `DD.MM.YYYY`, as in the 7GUIs task
+    Dotted,
+    /// The short date format of the system locale
+    Locale,
+}
+
+impl DateFormat {
+    pub const ALL: [DateFormat; 3] = [DateFormat::Iso, DateFormat::Dotted, DateFormat::Locale];
+
+    /// The chrono format string
+    pub fn pattern(self) -> &'static str {
+        match self {
+            DateFormat::Iso => DATE_FORMAT,
+            DateFormat::Dotted => "%d.%m.%Y",
+            DateFormat::Locale => locale_pattern(),
+        }
+    }
+
+    /// The pattern as users write it, such as `DD.MM.YYYY`
+    pub fn example(self) -> String {
+        let pattern = self.pattern().replace("%Y", "YYYY");
+        pattern.replace("%m", "MM").replace("%d", "DD")
+    }
+
+    /// A label for choosing this format
+    pub fn label(self) -> String {
+        match self {
+            DateFormat::Iso => format!("ISO ({})", self.example()),
+            DateFormat::Dotted => self.example(),
+            DateFormat::Locale => format!("Locale ({})", self.example()),
+        }
+    }
+
+    /// Parse a date, ignoring surrounding white-space
+    pub fn parse(self, text: &str) -> Result<NaiveDate, ParseError> {
+        NaiveDate::parse_from_str(text.trim(), self.pattern())
+    }
+
+    /// Format a date
+    pub fn format(self, date: NaiveDate) -> String {
+        date.format(self.pattern()).to_string()
+    }
+}
+
+/// The short date pattern of the locale named by `LC_ALL`, `LC_TIME` or `LANG`
+///
+/// Only common conventions are known; other locales use [`DATE_FORMAT`].
+fn locale_pattern() -> &'static str {
+    let vars = ["LC_ALL", "LC_TIME", "LANG"].into_iter();
+    let locale = vars
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty());
+    let locale = locale.unwrap_or_default();
+    // For example, "de_DE.UTF-8" or "sr_RS@latin"
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    let (language, region) = locale.split_once(['_', '-']).unwrap_or((locale, ""));
+    match (language, region) {
+        ("en", "US" | "PH") => "%m/%d/%Y",
+        ("ja" | "zh", _) => "%Y/%m/%d",
+        ("en" | "fr" | "es" | "it" | "pt" | "el", _) => "%d/%m/%Y",
+        ("de" | "da" | "fi" | "nb" | "no" | "pl" | "cs" | "sk" | "ru" | "uk" | "tr" | "ro", _) => {
+            "%d.%m.%Y"
+        }
+        ("nl", _) => "%d-%m-%Y",
+        _ => DATE_FORMAT,
+    }
+}
+
+/// Emitted by a [`DateField`] on each edit
+#[derive(Clone, Debug)]
+pub struct DateEdited<T> {
+    /// Identifies the field
+    pub tag: T,
+    pub result: Result<NaiveDate, ParseError>,
+}
+
+/// Guard for a [`DateField`] over input data `A`
+///
+/// Each edit is validated (setting the error state) and reported via
+/// [`DateEdited`]. When not being edited, the field shows the date taken
+/// from input data (if any), unless its text is already that date. When the
+/// [`DateFormat`] changes, a valid date is shown again in the new format.
+///
+/// The `value`, `disabled` and `invalid` getters are passed the field's tag,
+/// allowing one getter to serve several fields.
+#[derive(Debug)]
+pub struct DateGuard<A, T> {
+    tag: T,
+    value: fn(&A, &T) -> Option<NaiveDate>,
+    disabled: Option<fn(&A, &T) -> bool>,
+    format: Option<fn(&A) -> DateFormat>,
+    invalid: Option<fn(&A, &T) -> bool>,
+    /// The format of the shown text
+    shown: DateFormat,
+}
+
+impl<A, T> DateGuard<A, T> {
+    /// Construct with a `tag` (for [`DateEdited`]) and a `value` getter
+    pub fn new(tag: T, value: fn(&A, &T) -> Option<NaiveDate>) -> Self {
+        DateGuard {
+            tag,
+            value,
+            disabled: None,
+            format: None,
+            invalid: None,
+            shown: DateFormat::default(),
+        }
+    }
+
+    /// Disable the field whenever `disabled` returns true
+    pub fn with_disabled(mut self, disabled: fn(&A, &T) -> bool) -> Self {
+        self.disabled = Some(disabled);
+        self
+    }
+
+    /// Use the format returned by `format`, rather than [`DateFormat::Iso`]
+    pub fn with_format(mut self, format: fn(&A) -> DateFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Also set the error state whenever `invalid` returns true
+    ///
+    /// This marks a valid date which is not acceptable, such as one in the past.
+    pub fn with_invalid(mut self, invalid: fn(&A, &T) -> bool) -> Self {
+        self.invalid = Some(invalid);
+        self
+    }
+
+    fn format(&self, data: &A) -> DateFormat {
+        self.format.map(|format| format(data)).unwrap_or_default()
+    }
+}
+
+impl<A: 'static, T: Clone + Debug + 'static> EditGuard for DateGuard<A, T> {
+    type Data = A;
+
+    fn edit(edit: &mut EditField<Self>, cx: &mut EventCx, data: &A) {
+        let result = edit.guard.format(data).parse(edit.get_str());
+        let act = edit.set_error_state(result.is_err());
+        cx.action(edit.id(), act);
+
+        cx.push(DateEdited {
+            tag: edit.guard.tag.clone(),
+            result,
+        });
+    }
+
+    fn update(edit: &mut EditField<Self>, cx: &mut ConfigCx, data: &A) {
+        let format = edit.guard.format(data);
+        if format != edit.guard.shown {
+            if let Ok(date) = edit.guard.shown.parse(edit.get_str()) {
+                let act = edit.set_string(format.format(date));
+                cx.action(edit.id(), act);
+            }
+            edit.guard.shown = format;
+        }
+        if !edit.has_edit_focus() {
+            let value = (edit.guard.value)(data, &edit.guard.tag);
+            let shown = format.parse(edit.get_str()).ok();
+            if let Some(date) = value.filter(|date| shown != Some(*date)) {
+                let act = edit.set_string(format.format(date));
+                cx.action(edit.id(), act);
+            }
+        }
+        if let Some(invalid) = edit.guard.invalid {
+            let error = invalid(data, &edit.guard.tag) || format.parse(edit.get_str()).is_err();
+            let act = edit.set_error_state(error);
+            cx.action(edit.id(), act);
+        }
+        if let Some(disabled) = edit.guard.disabled {
+            cx.set_disabled(edit.id(), disabled(data, &edit.guard.tag));
+        }
+    }
+}
+
+impl_scope! {
+    /// A date-entry field
+    ///
+    /// Construct with `DateField::new(DateGuard::new(..))`.
+    ///
+    /// While the field has focus, Up and Down step a valid date by one day
+    /// (with Shift, by a week; with Ctrl, by a month). The change is reported
+    /// via [`DateEdited`] as if typed.
+    #[widget {
+        layout = self.edit;
+    }]
+    pub struct DateField<A: 'static, T: Clone + Debug + 'static> {
+        core: widget_core!(),
+        #[widget] edit: EditBox<DateGuard<A, T>>,
+        tag: T,
+        format: Option<fn(&A) -> DateFormat>,
+    }
+
+    impl Self {
+        /// Construct with a `guard`
+        pub fn new(guard: DateGuard<A, T>) -> Self {
+            DateField {
+                core: Default::default(),
+                tag: guard.tag.clone(),
+                format: guard.format,
+                edit: EditBox::new(guard),
+            }
+        }
+
+        /// Step the date forwards (`direction = 1`) or backwards (`-1`)
+        fn step(&mut self, cx: &mut EventCx, data: &A, direction: i32) -> IsUsed {
+            let format = self.format.map(|format| format(data)).unwrap_or_default();
+            let Ok(date) = format.parse(self.edit.get_str()) else {
+                return IsUsed::Unused;
+            };
+            let modifiers = cx.modifiers();
+            let date = if modifiers.control_key() {
+                let months = Months::new(1);
+                if direction < 0 {
+                    date.checked_sub_months(months)
+                } else {
+                    date.checked_add_months(months)
+                }
+            } else {
+                let days = if modifiers.shift_key() { 7 } else { 1 };
+                date.checked_add_signed(Duration::days(days * i64::from(direction)))
+            };
+            if let Some(date) = date {
+                let act = self.edit.set_string(format.format(date));
+                cx.action(self.edit.id(), act);
+                cx.push(DateEdited {
+                    tag: self.tag.clone(),
+                    result: Ok(date),
+                });
+            }
+            IsUsed::Used
+        }
+    }
+
+    impl Events for Self {
+        type Data = A;
+
+        fn handle_event(&mut self, cx: &mut EventCx, data: &A, event: Event) -> IsUsed {
+            // A single-line edit field does not use Up and Down
+            match event {
+                Event::Command(Command::Up, _) => self.step(cx, data, 1),
+                Event::Command(Command::Down, _) => self.step(cx, data, -1),
+                _ => IsUsed::Unused,
+            }
+        }
+    }
+}
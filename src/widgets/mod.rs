@@ -0,0 +1,14 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Entry widgets shared by several demos
+
+mod date;
+mod number;
+
+pub use date::{format_date, parse_date, DATE_FORMAT};
+pub use date::{DateEdited, DateField, DateFormat, DateGuard};
+pub use number::{approx_eq, format_number, parse_number, represents};
+pub use number::{NumberActivated, NumberEdited, NumberError, NumberField, NumberGuard};
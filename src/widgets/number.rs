@@ -0,0 +1,211 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Numeric entry
+
+use kas::prelude::*;
+use kas::widgets::{EditBox, EditField, EditGuard};
+use std::fmt::Debug;
+
+/// Parse a number, accepting common locale conventions
+///
+/// Either `.` or `,` may be the decimal separator: where both are used, the
+/// last is taken as decimal; a separator used more than once is taken as
+/// grouping (thus "1.000.000" and "1,000,000" are both one million, but
+/// "1,000" is one). Spaces, `_` and `'` are ignored as grouping, and `−`
+/// (U+2212) is accepted as a minus sign. Scientific notation is supported.
+pub fn parse_number(text: &str) -> Option<f64> {
+    let text: String = text
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '\'' | '\u{a0}' | '\u{202f}'))
+        .map(|c| if c == '−' { '-' } else { c })
+        .collect();
+    let decimal = text.rfind(['.', ',']).filter(|&i| {
+        let sep = char::from(text.as_bytes()[i]);
+        text.matches(sep).count() == 1
+    });
+
+    let mut normal = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        match c {
+            '.' | ',' if Some(i) == decimal => normal.push('.'),
+            '.' | ',' => (),
+            c => normal.push(c),
+        }
+    }
+    normal.parse().ok()
+}
+
+/// Format a value, using scientific notation for very large or small values
+///
+/// Other values are rounded to 9 decimal places, hiding round-trip noise such
+/// as `99.99999999999999`.
+pub fn format_number(value: f64) -> String {
+    let abs = value.abs();
+    if abs >= 1e9 || (abs != 0.0 && abs < 1e-4) {
+        format!("{value:e}")
+    } else {
+        ((value * 1e9).round() / 1e9).to_string()
+    }
+}
+
+/// Compare values with a relative tolerance
+pub fn approx_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+}
+
+/// True if `text` already represents `value`
+///
+/// Such text is left alone on update, so that e.g. "100.0" is not rewritten
+/// as "100".
+pub fn represents(text: &str, value: f64) -> bool {
+    parse_number(text).is_some_and(|x| approx_eq(x, value))
+}
+
+/// Reason a [`NumberField`]'s input is invalid
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NumberError {
+    Parse,
+    /// Less than the given minimum
+    BelowMin(f64),
+    /// Greater than the given maximum
+    AboveMax(f64),
+}
+
+impl std::fmt::Display for NumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberError::Parse => f.write_str("not a number"),
+            NumberError::BelowMin(min) => write!(f, "less than {}", format_number(*min)),
+            NumberError::AboveMax(max) => write!(f, "greater than {}", format_number(*max)),
+        }
+    }
+}
+
+/// Emitted by a [`NumberField`] on each edit
+#[derive(Clone, Debug)]
+pub struct NumberEdited<T> {
+    /// Identifies the field
+    pub tag: T,
+    pub result: Result<f64, NumberError>,
+}
+
+/// Emitted by a [`NumberField`] when Enter is pressed on valid input
+#[derive(Clone, Debug)]
+pub struct NumberActivated<T> {
+    /// Identifies the field
+    pub tag: T,
+    pub value: f64,
+}
+
+/// Guard for a [`NumberField`] over input data `A`
+///
+/// Input is validated on each edit, setting the error state, and reported
+/// via [`NumberEdited`]. While not being edited, the field shows the value
+/// taken from input data (unless its text already represents that value).
+#[derive(Debug)]
+pub struct NumberGuard<A, T> {
+    tag: T,
+    value: fn(&A) -> f64,
+    limits: fn(&A) -> (f64, f64),
+    step: Option<f64>,
+    format: fn(f64) -> String,
+}
+
+impl<A, T> NumberGuard<A, T> {
+    /// Construct with a `tag` (for messages) and a `value` getter
+    ///
+    /// By default, any finite value is allowed and values are formatted with
+    /// [`format_number`].
+    pub fn new(tag: T, value: fn(&A) -> f64) -> Self {
+        NumberGuard {
+            tag,
+            value,
+            limits: |_| (f64::NEG_INFINITY, f64::INFINITY),
+            step: None,
+            format: format_number,
+        }
+    }
+
+    /// Set the (inclusive) minimum and maximum, which may depend on input data
+    pub fn with_limits(mut self, limits: fn(&A) -> (f64, f64)) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Round input to the nearest multiple of `step`
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    /// Set the format used to display values
+    pub fn with_format(mut self, format: fn(f64) -> String) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Parse and validate `text`
+    pub fn validate(&self, data: &A, text: &str) -> Result<f64, NumberError> {
+        let mut value = parse_number(text)
+            .filter(|v| v.is_finite())
+            .ok_or(NumberError::Parse)?;
+        if let Some(step) = self.step {
+            value = (value / step).round() * step;
+        }
+        let (min, max) = (self.limits)(data);
+        if value < min {
+            Err(NumberError::BelowMin(min))
+        } else if value > max {
+            Err(NumberError::AboveMax(max))
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+impl<A: 'static, T: Clone + Debug + 'static> EditGuard for NumberGuard<A, T> {
+    type Data = A;
+
+    fn edit(edit: &mut EditField<Self>, cx: &mut EventCx, data: &A) {
+        let result = edit.guard.validate(data, edit.get_str());
+        let act = edit.set_error_state(result.is_err());
+        cx.action(edit.id(), act);
+
+        cx.push(NumberEdited {
+            tag: edit.guard.tag.clone(),
+            result,
+        });
+    }
+
+    fn activate(edit: &mut EditField<Self>, cx: &mut EventCx, data: &A) -> IsUsed {
+        if let Ok(value) = edit.guard.validate(data, edit.get_str()) {
+            cx.push(NumberActivated {
+                tag: edit.guard.tag.clone(),
+                value,
+            });
+        }
+        IsUsed::Used
+    }
+
+    fn update(edit: &mut EditField<Self>, cx: &mut ConfigCx, data: &A) {
+        // Don't replace text the user is currently typing, nor text which
+        // already represents the value (e.g. "100.0" vs "100")
+        if !edit.has_edit_focus() {
+            let value = (edit.guard.value)(data);
+            let mut act = edit.set_error_state(false);
+            if !represents(edit.get_str(), value) {
+                act |= edit.set_string((edit.guard.format)(value));
+            }
+            cx.action(edit, act);
+        }
+    }
+}
+
+/// A validated numeric-entry field
+///
+/// Construct with `NumberField::new(NumberGuard::new(..))`.
+pub type NumberField<A, T> = EditBox<NumberGuard<A, T>>;
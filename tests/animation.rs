@@ -0,0 +1,53 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Animation helpers
+
+use kas_7guis::animation::{ease_out_cubic, linear, Tween};
+use std::time::Duration;
+
+const LONG: Duration = Duration::from_secs(3600);
+
+#[test]
+fn easing() {
+    for ease in [linear, ease_out_cubic] {
+        assert_eq!(ease(0.0), 0.0);
+        assert_eq!(ease(1.0), 1.0);
+    }
+    assert!(ease_out_cubic(0.5) > 0.5);
+}
+
+#[test]
+fn tween() {
+    let mut tween = Tween::new(0.0, LONG);
+    assert!(!tween.is_running());
+    assert_eq!(tween.value(), 0.0);
+
+    tween.set(1.0);
+    assert!(tween.is_running());
+    assert_eq!(tween.target(), 1.0);
+    assert!(tween.value() < 0.01);
+
+    // A running animation continues towards the new target
+    tween.retarget(2.0);
+    assert!(tween.is_running());
+    assert!(tween.value() < 0.01);
+
+    tween.jump(3.0);
+    assert!(!tween.is_running());
+    assert_eq!(tween.value(), 3.0);
+
+    // When at rest, the value follows the target
+    tween.retarget(4.0);
+    assert_eq!(tween.value(), 4.0);
+}
+
+#[test]
+fn zero_duration() {
+    let mut tween = Tween::new(0.0, Duration::ZERO);
+    tween.set(1.0);
+    assert!(!tween.is_running());
+    assert_eq!(tween.value(), 1.0);
+}
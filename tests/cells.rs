@@ -0,0 +1,287 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Cells formula evaluation, including inputs found by fuzzing
+
+use kas_7guis::cells::{evaluate_column, evaluate_column_with_names, offset_input};
+use kas_7guis::cells::{CellData, Key};
+
+#[test]
+fn evaluate() {
+    let values = evaluate_column("3\n4\n= A1 + A2 * 2\n=-A3\n=(a1 - 1) / 4\ntext");
+    assert_eq!(values, ["3", "4", "11", "-11", "0.5", "text"]);
+}
+
+#[test]
+fn functions() {
+    let input =
+        "1\n2\ntext\n\n=SUM(A1:A4)\n=avg(A1:A2, 6)\n=MIN(A2:A1)\n=MAX(A1:A3) * 2\n=COUNT(A1:A4)";
+    let values = evaluate_column(input);
+    assert_eq!(&values[4..], ["3", "3", "1", "4", "2"]);
+
+    // A range including the cell itself is a cycle
+    assert_eq!(evaluate_column("1\n=SUM(A1:A2)"), ["1", "#CYCLE!"]);
+    assert_eq!(evaluate_column("=SUM()"), ["0"]);
+
+    // Large ranges over a sparse sheet
+    assert_eq!(evaluate_column("2\n3\n=SUM(A1:ZZ2)"), ["2", "3", "5"]);
+    assert_eq!(evaluate_column("2\n=COUNT(B1:ZZ99999) + A1"), ["2", "2"]);
+    assert_eq!(evaluate_column("=SUM(A1:A0)"), ["BAD FORMULA"]);
+}
+
+#[test]
+fn library() {
+    let cases = [
+        ("=ABS(-2.5)", "2.5"),
+        ("=ROUND(2.346, 2)", "2.35"),
+        ("=round(1234.5)", "1235"),
+        ("=ROUND(1234, -2)", "1200"),
+        ("=SQRT(16)", "4"),
+        ("=SIN(0) + COS(0)", "1"),
+        ("=IF(1 < 2, \"yes\", \"x\" * 2)", "yes"),
+        ("=IF(0, 1, 2)", "2"),
+        ("=2 >= 3", "0"),
+        ("=(1 < 2) < 3", "1"),
+        ("=\"a\" = \"A\"", "1"),
+        ("=\"a\" <> 1", "1"),
+        ("=\"a\" < 1", "#VALUE!"),
+        ("=1 < 2 < 3", "BAD FORMULA"),
+        ("=ABS(1, 2)", "BAD FORMULA"),
+        ("=ABS(A2:A3)", "BAD FORMULA"),
+        ("=IF(1, 2)", "BAD FORMULA"),
+        ("=FOO(1)", "BAD FORMULA"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(evaluate_column(input), [expected], "{input}");
+    }
+}
+
+#[test]
+fn precedence() {
+    let cases = [
+        ("=2 ^ 3 ^ 2", "512"),
+        ("=-2 ^ 2", "-4"),
+        ("=(-2) ^ 2", "4"),
+        ("=2 * -3", "-6"),
+        ("=2 ^ -1", "0.5"),
+        ("=1 - 2 ^ 2 * 3", "-11"),
+        ("=-3 * 2 ^ 2", "-12"),
+        ("=6 / 2 ^ 2 + 1", "2.5"),
+        ("=2 ^", "BAD FORMULA"),
+        ("=2 * - -3", "BAD FORMULA"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(evaluate_column(input), [expected], "{input}");
+    }
+    assert_eq!(evaluate_column("3\n=-A1 ^ 2"), ["3", "-9"]);
+}
+
+#[test]
+fn text() {
+    let input = "5\n=\"Total: \" & A1\n=\"say \"\"hi\"\"\"\n=1 & 2 + 3\nword\n=A5 & \"s\"";
+    let values = evaluate_column(input);
+    assert_eq!(
+        values,
+        ["5", "Total: 5", "say \"hi\"", "15", "word", "words"]
+    );
+
+    let values = evaluate_column("text\n=A1 + 1\n=A2 * 2\n=SUM(A1:A2)");
+    assert_eq!(values, ["text", "#VALUE!", "#REF!", "0"]);
+}
+
+#[test]
+fn formats() {
+    let input = "=0.1 + 0.2\n=1e20 / 3\n[0.00]=1/3\n[0%]0.256\n[0.0E]12345\n[0.00]text\n[x]1";
+    let values = evaluate_column(input);
+    assert_eq!(
+        values,
+        [
+            "0.3",
+            "3.33333333333333e19",
+            "0.33",
+            "26%",
+            "1.2e4",
+            "text",
+            "[x]1"
+        ]
+    );
+    assert_eq!(
+        evaluate_column("[0.0000000000000000]1"),
+        ["[0.0000000000000000]1"]
+    );
+}
+
+#[test]
+fn bad_references() {
+    for input in ["=A999999", "=A0", "=A100000", "=A1", "=AAA1", "=zz99999"] {
+        let values = evaluate_column(input);
+        assert_eq!(values.len(), 1, "{input}");
+    }
+    assert_eq!(evaluate_column("=A100000"), ["BAD FORMULA"]);
+    assert_eq!(evaluate_column("=AAA1"), ["BAD FORMULA"]);
+    assert_eq!(evaluate_column("=zz99999"), ["#REF!"]);
+    assert_eq!(evaluate_column("=A1"), ["#CYCLE!"]);
+    assert_eq!(evaluate_column("=A2"), ["#REF!"]);
+}
+
+#[test]
+fn wide_levels() {
+    // Each level is larger than the threshold for parallel evaluation
+    let mut input = "2\n".to_string();
+    input.push_str(&"=A1 * 2\n".repeat(1000));
+    input.push_str(&"=A2 + 1\n".repeat(1000));
+    input.push_str("=SUM(A2:A2001)\n=A1 + x");
+    let values = evaluate_column(&input);
+    assert_eq!(values.len(), 2003);
+    assert!(values[1..=1000].iter().all(|v| v == "4"));
+    assert!(values[1001..=2000].iter().all(|v| v == "5"));
+    assert_eq!(values[2001..], ["9000", "#NAME?"]);
+}
+
+#[test]
+fn cycles() {
+    // Only A2 and A3 form a cycle; A1 and A4 depend on it
+    let values = evaluate_column("=A2\n=A3 + 1\n=A2\n=A1 * 2\n5");
+    assert_eq!(values, ["#REF!", "#CYCLE!", "#CYCLE!", "#REF!", "5"]);
+}
+
+#[test]
+fn absolute_references() {
+    assert_eq!(evaluate_column("2\n=$A$1 * a$1 + $A1"), ["2", "6"]);
+    assert_eq!(evaluate_column("=$A"), ["BAD FORMULA"]);
+    assert_eq!(evaluate_column("=A$$1"), ["BAD FORMULA"]);
+}
+
+#[test]
+fn names() {
+    let names = [
+        ("tax_rate", "A1"),
+        ("items", "$A$2:A3"),
+        ("A2", "A1"),
+        ("bad", "A0"),
+    ];
+    let input = "0.2\n10\n30\n=SUM(items) * (1 + tax_rate)\n=_x + 1\n=items\n=bad";
+    let values = evaluate_column_with_names(input, &names);
+    assert_eq!(
+        values,
+        ["0.2", "10", "30", "48", "#NAME?", "#VALUE!", "#NAME?"]
+    );
+
+    assert_eq!(evaluate_column("=tax_rate * 2"), ["#NAME?"]);
+    assert_eq!(evaluate_column("=x\n=A1 + 1"), ["#NAME?", "#REF!"]);
+    assert_eq!(evaluate_column("=SUM(a_1)"), ["#NAME?"]);
+    assert_eq!(evaluate_column("=rate2024"), ["BAD FORMULA"]);
+    assert_eq!(
+        offset_input("=tax_rate * A1", 0, 1).as_deref(),
+        Some("=tax_rate * A2")
+    );
+}
+
+#[test]
+fn fill() {
+    let cases = [
+        ("=A2 + 1", 0, 1, "=A3 + 1"),
+        ("=  a1*b1", 0, 1, "=A2 * B2"),
+        ("[0.00]=SUM(A1:B2) * 2", 1, 0, "[0.00]=SUM(B1:C2) * 2"),
+        (
+            "=-(A1 - B1) ^ 2 & \"x\"\"\"",
+            0,
+            1,
+            "=-(A2 - B2) ^ 2 & \"x\"\"\"",
+        ),
+        ("=1 - (2 - 3) / (4 * 5)", 1, 1, "=1 - (2 - 3) / (4 * 5)"),
+        ("=(2 ^ 3) ^ 2 - -1", 0, 0, "=(2 ^ 3) ^ 2 - -1"),
+        ("=IF(A1 >= 0, A1, -A1)", 0, 1, "=IF(A2 >= 0, A2, -A2)"),
+        ("text", 1, 1, "text"),
+        ("=A1 +", 0, 1, "=A1 +"),
+    ];
+    for (input, cols, rows, expected) in cases {
+        assert_eq!(offset_input(input, cols, rows).as_deref(), Some(expected));
+    }
+    assert_eq!(offset_input("=B1", 0, -1), None);
+    assert_eq!(
+        offset_input("=$A$1 + a$1 * $a1", 1, 1).as_deref(),
+        Some("=$A$1 + B$1 * $A2")
+    );
+    assert_eq!(
+        offset_input("=SUM($ZZ1:$A$1)", 1, 0).as_deref(),
+        Some("=SUM($ZZ1:$A$1)")
+    );
+    assert_eq!(offset_input("=SUM(A1:ZZ9)", 1, 0), None);
+}
+
+#[test]
+fn deep_nesting() {
+    let input = format!("={}1{}", "(".repeat(10_000), ")".repeat(10_000));
+    assert_eq!(evaluate_column(&input), ["BAD FORMULA"]);
+}
+
+#[test]
+fn many_powers() {
+    let input = format!("=2{}", "^1".repeat(10_000));
+    assert_eq!(evaluate_column(&input), ["BAD FORMULA"]);
+    assert_eq!(
+        evaluate_column("=\"^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^\""),
+        ["^".repeat(34)]
+    );
+}
+
+#[test]
+fn extreme_numbers() {
+    assert_eq!(evaluate_column("=1e999"), ["inf"]);
+    assert_eq!(evaluate_column("=1/0 - 1/0"), ["NaN"]);
+}
+
+#[test]
+fn recompute_on_edit() {
+    let key = |s| Key::parse(s).unwrap();
+    let mut data = CellData::new();
+    data.set_input(key("A1"), "2".to_string()).unwrap();
+    data.set_input(key("B1"), "=A1 * 3".to_string()).unwrap();
+    data.set_input(key("C1"), "=SUM(A1:B1)".to_string())
+        .unwrap();
+    assert_eq!(data.display(key("B1")), "6");
+    assert_eq!(data.display(key("C1")), "8");
+
+    // Dependents, including those via ranges, are updated
+    data.set_input(key("A1"), "5".to_string()).unwrap();
+    assert_eq!(data.display(key("B1")), "15");
+    assert_eq!(data.display(key("C1")), "20");
+
+    data.set_input(key("A1"), "text".to_string()).unwrap();
+    assert_eq!(data.display(key("B1")), "#VALUE!");
+
+    data.set_input(key("A1"), "=B1".to_string()).unwrap();
+    assert_eq!(data.display(key("A1")), "#CYCLE!");
+    assert_eq!(data.display(key("B1")), "#CYCLE!");
+
+    // Breaking the cycle recovers
+    data.set_input(key("A1"), "1".to_string()).unwrap();
+    assert_eq!(data.display(key("B1")), "3");
+    assert_eq!(data.display(key("C1")), "4");
+
+    assert!(data.set_input(key("D1"), "=1 +".to_string()).is_err());
+    assert_eq!(data.display(key("D1")), "BAD FORMULA");
+    assert_eq!(data.display(key("E1")), "");
+}
+
+#[test]
+fn copy_rectangle() {
+    use kas_7guis::clipboard::to_tsv;
+    let key = |s| Key::parse(s).unwrap();
+    let mut data = CellData::new();
+    data.set_input(key("A1"), "1".to_string()).unwrap();
+    data.set_input(key("B2"), "=A1 + 1".to_string()).unwrap();
+    data.set_input(key("C3"), "x".to_string()).unwrap();
+
+    // Corners may be given in any order
+    let rows = data.range_inputs(key("B2"), key("A1"));
+    assert_eq!(rows, [["1", ""], ["", "=A1 + 1"]]);
+    assert_eq!(to_tsv(rows), "1\t\n\t=A1 + 1\n");
+
+    // Beyond the last used row and column is omitted
+    let rows = data.range_inputs(key("B2"), key("Z100"));
+    assert_eq!(rows, [["=A1 + 1", ""], ["", "x"]]);
+}
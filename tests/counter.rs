@@ -0,0 +1,59 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Counter state and sparkline
+
+use kas_7guis::counter::{sparkline_fractions, Counter};
+
+#[test]
+fn sparkline() {
+    assert_eq!(sparkline_fractions(&[]), [] as [f32; 0]);
+    assert_eq!(sparkline_fractions(&[5, 5]), [0.0, 0.0]);
+    assert_eq!(sparkline_fractions(&[0, 2, 1]), [0.0, 1.0, 0.5]);
+}
+
+#[test]
+fn sparkline_full_range() {
+    let values = [i32::MIN, -2_000_000_000, 0, 2_000_000_000, i32::MAX];
+    let fractions = sparkline_fractions(&values);
+    assert_eq!(fractions[0], 0.0);
+    assert_eq!(fractions[4], 1.0);
+    assert!(fractions.windows(2).all(|w| w[0] < w[1]));
+    assert!((fractions[2] - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn increment_and_undo() {
+    let mut counter = Counter::default();
+    assert_eq!(counter.count(), 0);
+    assert!(!counter.can_undo());
+
+    counter.change(1);
+    counter.change(1);
+    counter.set_count(10);
+    counter.set_count(10);
+    assert_eq!(counter.count(), 10);
+    assert_eq!(counter.history(), [0, 1, 2, 10]);
+
+    counter.undo();
+    assert_eq!(counter.count(), 2);
+    counter.undo();
+    counter.undo();
+    assert_eq!(counter.count(), 0);
+    assert!(!counter.can_undo());
+}
+
+#[test]
+fn overflow_ignored() {
+    let mut counter = Counter::default();
+    counter.set_count(i32::MAX);
+    counter.change(1);
+    assert_eq!(counter.count(), i32::MAX);
+    counter.set_count(i32::MIN);
+    counter.change(-1);
+    assert_eq!(counter.count(), i32::MIN);
+    counter.undo();
+    assert_eq!(counter.count(), i32::MAX);
+}
@@ -0,0 +1,164 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Headless tests over the demo registry
+//!
+//! Widgets and windows may be constructed without a display, but kas 0.14
+//! has no headless event loop, so events cannot (yet) be injected. Instead,
+//! the state changed by messages is tested directly in the other test files.
+
+use kas_7guis::config::Config;
+use kas_7guis::demos::{numbered_title, Demo, DEMOS};
+
+#[test]
+fn construct_windows() {
+    for demo in DEMOS {
+        assert_eq!(
+            demo.window().is_some(),
+            demo.is_available(),
+            "{}",
+            demo.name
+        );
+        if let Some((_, handle)) = demo.tracked_window(2) {
+            // The window was dropped immediately
+            assert!(!handle.is_open());
+        }
+    }
+}
+
+#[test]
+fn construct_stress_window() {
+    let _ = kas_7guis::cells::stress_window(1);
+}
+
+#[test]
+fn find_demos() {
+    for demo in DEMOS {
+        let found = Demo::find(demo.name).unwrap();
+        assert!(std::ptr::eq(found, demo));
+    }
+    assert!(Demo::find("no-such-demo").is_none());
+}
+
+#[test]
+fn instance_titles() {
+    assert_eq!(numbered_title("Timer", 1), "Timer");
+    assert_eq!(numbered_title("Timer", 2), "Timer (2)");
+}
+
+#[test]
+fn config_round_trip() {
+    let mut config = Config::default();
+    config.scale = 150;
+    config.open_demos = vec!["counter".to_string(), "cells".to_string()];
+    let text = toml::to_string_pretty(&config).unwrap();
+    assert_eq!(toml::from_str::<Config>(&text).unwrap(), config);
+
+    // Missing fields take default values
+    let config: Config = toml::from_str("[demos.timer]\nduration = 15.0\n").unwrap();
+    assert_eq!(config.scale, 100);
+    assert_eq!(config.demo_option::<f64>("timer", "duration"), Some(15.0));
+}
+
+#[test]
+fn crud_csv_round_trip() {
+    use kas_7guis::crud::{parse_csv, to_csv, Entry};
+    let entries = vec![
+        Entry::new("Mustermann", "Max"),
+        Entry::from_fields("Doe, Jr.", "Jane", "jane@example.com", "1990-02-28").unwrap(),
+    ];
+    let text = to_csv(&entries).unwrap();
+    let imported = parse_csv(&text).unwrap();
+    assert_eq!(imported.skipped, 0);
+    let shown: Vec<_> = imported.entries.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        shown,
+        [
+            "Mustermann, Max",
+            "Doe, Jr., Jane <jane@example.com> (born 1990-02-28)"
+        ]
+    );
+
+    // Rows without a surname or with an invalid email or date are skipped
+    let text = "Emil,Hans\n,Nobody\nTisch,Roman,not-an-email\nX,Y,,1990-13-01\nSolo\n";
+    let imported = parse_csv(text).unwrap();
+    assert_eq!(imported.entries.len(), 2);
+    assert_eq!(imported.skipped, 3);
+}
+
+#[test]
+fn crud_vcard_round_trip() {
+    use kas_7guis::crud::{parse_vcards, to_vcards, Entry};
+    let entries = vec![
+        Entry::new("Emil", "Hans"),
+        Entry::from_fields("Smith; Jones", "Ann", "ann@example.org", "2001-09-11").unwrap(),
+    ];
+    let imported = parse_vcards(&to_vcards(&entries));
+    assert_eq!(imported.skipped, 0);
+    let shown: Vec<_> = imported.entries.iter().map(|e| e.to_string()).collect();
+    let expected: Vec<_> = entries.iter().map(|e| e.to_string()).collect();
+    assert_eq!(shown, expected);
+
+    // Folded lines, groups, parameters and basic-format dates
+    let text = "BEGIN:VCARD\nVERSION:4.0\nN:Muster\n mann;Max;;;\n\
+                item1.EMAIL;TYPE=work:max@example.com\nBDAY:19800101\nEND:VCARD\n\
+                BEGIN:VCARD\nFN:No Surname\nEND:VCARD\n";
+    let imported = parse_vcards(text);
+    assert_eq!(imported.skipped, 1);
+    assert_eq!(
+        imported.entries[0].to_string(),
+        "Mustermann, Max <max@example.com> (born 1980-01-01)"
+    );
+}
+
+#[test]
+fn crud_reused_row_deletes_its_entry() {
+    use kas::view::Driver;
+    use kas_7guis::crud::{menu_entry, remove_entries, Entry, ListGuard};
+    use std::collections::BTreeSet;
+    let mut entries = vec![
+        Entry::new("Emil", "Hans"),
+        Entry::new("Mustermann", "Max"),
+        Entry::new("Tisch", "Roman"),
+    ];
+    let mut driver = ListGuard::default();
+    let mut row = driver.make(&0);
+
+    // Filtering for "Tisch" leaves only entry 2, shown by the first row
+    driver.set_key(&mut row, &2);
+    assert_eq!(row.inner.key(), 2);
+
+    // Its context menu (opened with the row's key) targets the entry shown;
+    // Delete selects only that entry, then removes the selection
+    let index = menu_entry(Some(row.inner.key()), &entries).unwrap();
+    assert_eq!(index, 2);
+    remove_entries(&mut entries, &BTreeSet::from([index]));
+    let shown: Vec<_> = entries.iter().map(|e| e.to_string()).collect();
+    assert_eq!(shown, ["Emil, Hans", "Mustermann, Max"]);
+
+    // An action from a menu opened before the entry was removed is ignored
+    assert_eq!(menu_entry(Some(index), &entries), None);
+    assert_eq!(menu_entry(None, &entries), None);
+}
+
+#[test]
+fn construct_large_crud_window() {
+    let _ = kas_7guis::crud::large_window(1000);
+}
+
+#[test]
+fn date_formats() {
+    use chrono::NaiveDate;
+    use kas_7guis::widgets::DateFormat;
+    let date = NaiveDate::from_ymd_opt(2024, 3, 7).unwrap();
+    assert_eq!(DateFormat::Iso.format(date), "2024-03-07");
+    assert_eq!(DateFormat::Dotted.format(date), "07.03.2024");
+    assert_eq!(DateFormat::Dotted.parse(" 7.3.2024 "), Ok(date));
+    assert!(DateFormat::Dotted.parse("2024-03-07").is_err());
+    assert_eq!(DateFormat::Dotted.example(), "DD.MM.YYYY");
+    for format in DateFormat::ALL {
+        assert_eq!(format.parse(&format.format(date)), Ok(date));
+    }
+}
@@ -0,0 +1,84 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Flight booker validation, with a fixed "today"
+
+use chrono::{NaiveDate, NaiveTime};
+use kas_7guis::flight_booker::{Data, Field, Flight};
+use kas_7guis::widgets::DateFormat;
+
+fn date(text: &str) -> NaiveDate {
+    DateFormat::Iso.parse(text).unwrap()
+}
+
+fn booker() -> Data {
+    let now = date("2026-03-04").and_hms_opt(12, 0, 0).unwrap();
+    Data::new(Some(now))
+}
+
+#[test]
+fn one_way() {
+    let mut data = booker();
+    assert!(data.can_book());
+    assert_eq!(data.error_text(), "");
+
+    data.set_date(Field::Out, Ok(date("2026-03-03")));
+    assert!(!data.can_book());
+    assert_eq!(data.error_text(), "Error: outbound date is before today!");
+    assert_eq!(data.problem(Field::Out).as_deref(), Some("before today"));
+
+    data.set_date(Field::Out, DateFormat::Iso.parse("2026-02-30"));
+    assert!(!data.can_book());
+    assert!(data.error_text().starts_with("Error: outbound date: "));
+
+    data.set_date(Field::Out, Ok(date("2026-03-04")));
+    assert!(data.can_book());
+}
+
+#[test]
+fn return_flight() {
+    let mut data = booker();
+    data.set_date(Field::Return, Ok(date("2026-03-01")));
+    // The return date is unused by a one-way flight
+    assert!(data.can_book());
+
+    data.set_flight(Flight::Return);
+    assert!(!data.can_book());
+    assert_eq!(
+        data.error_text(),
+        "Error: return date must be after outbound date!"
+    );
+
+    data.set_date(Field::Return, Ok(date("2026-03-04")));
+    assert!(data.can_book());
+}
+
+#[test]
+fn multi_city() {
+    let mut data = booker();
+    data.set_flight(Flight::MultiCity);
+    assert!(data.can_book());
+
+    data.set_date(Field::Leg(0), Ok(date("2026-03-03")));
+    assert_eq!(data.error_text(), "Error: leg 2 must not be before leg 1!");
+
+    data.set_date(Field::Leg(0), Ok(date("2026-03-05")));
+    assert!(data.can_book());
+}
+
+#[test]
+fn departure_time() {
+    let mut data = booker();
+    data.set_time(Ok(Some(NaiveTime::from_hms_opt(11, 0, 0).unwrap())));
+    assert_eq!(data.error_text(), "Error: departure time has passed!");
+
+    data.set_time(Ok(Some(NaiveTime::from_hms_opt(13, 0, 0).unwrap())));
+    assert!(data.can_book());
+
+    // Tomorrow, any time will do
+    data.set_time(Ok(Some(NaiveTime::from_hms_opt(11, 0, 0).unwrap())));
+    data.set_date(Field::Out, Ok(date("2026-03-05")));
+    assert!(data.can_book());
+}
@@ -0,0 +1,58 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Temperature conversion round-trip stability
+
+use kas_7guis::temp_conv::{celsius_to_fahrenheit, fahrenheit_to_celsius};
+use kas_7guis::widgets::{approx_eq, format_number, parse_number, represents};
+
+#[test]
+fn boiling_point() {
+    let f = celsius_to_fahrenheit(100.0);
+    assert!(approx_eq(f, 212.0));
+    assert_eq!(format_number(f), "212");
+
+    let c = fahrenheit_to_celsius(212.0);
+    assert!(approx_eq(c, 100.0));
+    assert_eq!(format_number(c), "100");
+}
+
+#[test]
+fn epsilon() {
+    assert!(approx_eq(100.0, 99.99999999999999));
+    assert!(approx_eq(0.0, 1e-12));
+    assert!(approx_eq(1e12, 1e12 + 1.0));
+    assert!(!approx_eq(100.0, 100.001));
+    assert!(!approx_eq(0.0, 1e-6));
+}
+
+#[test]
+fn equivalent_text_kept() {
+    let value = fahrenheit_to_celsius(celsius_to_fahrenheit(100.0));
+    assert!(represents("100.0", value));
+    assert!(represents(" 100 ", value));
+    assert!(represents("1e2", value));
+    assert!(!represents("100.1", value));
+    assert!(!represents("", value));
+}
+
+#[test]
+fn formatting_round_trip() {
+    for text in ["100", "-40", "37.5", "0", "-273.15", "0.1"] {
+        let value = parse_number(text).unwrap();
+        let back = fahrenheit_to_celsius(celsius_to_fahrenheit(value));
+        assert_eq!(format_number(back), text);
+    }
+}
+
+#[test]
+fn no_drift() {
+    let mut c = 100.0;
+    for _ in 0..1000 {
+        c = fahrenheit_to_celsius(celsius_to_fahrenheit(c));
+        assert_eq!(format_number(c), "100");
+    }
+    assert!(represents("100.0", c));
+}